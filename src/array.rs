@@ -0,0 +1,61 @@
+use ndarray::{ArrayD, IxDyn};
+
+use crate::reader::*;
+
+impl<T> NCVariableContainer<T>
+where
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+{
+    /// The data as an N-dimensional array shaped by the variable's
+    /// dimensions in `file`, with the unlimited dimension taking the
+    /// current record count. A scalar variable gives a 0-dimensional array.
+    /// Fails if the variable holds fewer elements than its shape needs.
+    pub fn to_ndarray(&self, file: &NCFile) -> Result<ArrayD<T>> {
+        let shape = self.shape(file);
+        let count = shape.iter().product();
+
+        let values: Vec<T> = self.data.iter().take(count).collect();
+        if values.len() < count {
+            return Err(ParseError::new(&format!(
+                "variable {} holds {} of the {} elements its shape needs", self.name, values.len(), count
+            )));
+        }
+
+        ArrayD::from_shape_vec(IxDyn(&shape), values).map_err(|e| ParseError::new(&e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    #[test]
+    fn it_builds_ndarrays() {
+        let values: Vec<f32> = (0..6).map(|v| v as f32).collect();
+        let f = file(vec![dim("lat", 2), dim("lon", 3)], Vec::new());
+
+        let a = float_container("grid", &[0, 1], &values).to_ndarray(&f).unwrap();
+        assert_eq!(a.shape(), [2, 3]);
+        assert_eq!(a[[1, 2]], 5.0);
+        assert_eq!(a[[0, 1]], 1.0);
+
+        assert!(float_container("grid", &[0, 1], &values[..5]).to_ndarray(&f).is_err());
+    }
+
+    #[test]
+    fn it_shapes_sample_variables() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+
+        match f.variable("unknown").unwrap() {
+            NCVariable::Short(c) => {
+                let a = c.to_ndarray(&f).unwrap();
+                assert_eq!(a.shape(), [25, 400, 700]);
+                assert_eq!(a[[0, 0, 1]], c.data.get(1).unwrap());
+            }
+            _ => panic!("unknown isn't Short"),
+        }
+    }
+}
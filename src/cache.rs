@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use crate::reader::*;
+
+/// A lazily read file that keeps the data of only the most recently
+/// accessed variables in memory.
+#[derive(Debug)]
+pub struct NCCachedFile {
+    header: NCFile,
+    file: fs::File,
+    capacity: usize,
+    /// Materialized variables, least recently used first.
+    cache: VecDeque<NCVariable>,
+}
+
+impl NCFile {
+    /// Opens the file at `path`, reading only its header. Variable data is
+    /// read on access, and the data of the `capacity` most recently accessed
+    /// variables is cached. A capacity of zero is treated as one.
+    pub fn open_cached<P: AsRef<Path>>(path: P, capacity: usize) -> Result<NCCachedFile> {
+        let mut file = fs::File::open(path)?;
+        let options = ReaderOptions {
+            max_elements: Some(0),
+            ..ReaderOptions::default()
+        };
+        let header = Self::parse(&mut file, &options)?.0;
+
+        Ok(NCCachedFile {
+            header,
+            file,
+            capacity: capacity.max(1),
+            cache: VecDeque::new(),
+        })
+    }
+}
+
+impl NCCachedFile {
+    /// The parsed header. Its variables hold no data.
+    pub fn header(&self) -> &NCFile {
+        &self.header
+    }
+
+    /// Whether the data of the variable `name` is currently cached.
+    pub fn is_cached(&self, name: &str) -> bool {
+        self.cache.iter().any(|v| v.has_name(name))
+    }
+
+    /// The variable `name` with its data, reading the data if it isn't
+    /// cached and evicting the least recently used variable if the cache
+    /// is full.
+    pub fn variable(&mut self, name: &str) -> Result<&NCVariable> {
+        match self.cache.iter().position(|v| v.has_name(name)) {
+            Some(i) => {
+                let var = self.cache.remove(i).unwrap();
+                self.cache.push_back(var);
+            }
+            None => {
+                let mut var = self
+                    .header
+                    .variable(name)
+                    .ok_or_else(|| ParseError::new(&format!("no variable named {}", name)))?
+                    .clone();
+
+                let raw = self.header.data_extent(&var).read(&mut self.file, usize::MAX, false, None)?;
+                each_variable!(&mut var, c => c.data = NCData::new(raw));
+
+                if self.cache.len() == self.capacity {
+                    self.cache.pop_front();
+                }
+                self.cache.push_back(var);
+            }
+        }
+
+        Ok(self.cache.back().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    #[test]
+    fn it_evicts_least_recently_used_variables() {
+        let mut f = NCFile::open_cached(SAMPLE_FILE_1, 2).unwrap();
+        assert_eq!(f.header().variables.len(), 4);
+        assert!(!f.is_cached("longitude"));
+
+        f.variable("longitude").unwrap();
+        f.variable("latitude").unwrap();
+        f.variable("longitude").unwrap();
+        f.variable("time").unwrap();
+        assert!(f.is_cached("longitude"));
+        assert!(!f.is_cached("latitude"));
+        assert!(f.is_cached("time"));
+
+        f.variable("unknown").unwrap();
+        assert!(!f.is_cached("longitude"));
+
+        match f.variable("longitude").unwrap() {
+            NCVariable::Float(n) => {
+                assert!(!n.data.is_partial());
+                assert_eq!(n.data.iter().count(), 700);
+                assert_eq!(n.data.iter().next(), Some(-24.95));
+            }
+            _ => panic!("longitude isn't Float"),
+        }
+        assert!(f.variable("missing").is_err());
+    }
+}
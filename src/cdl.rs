@@ -0,0 +1,787 @@
+use std::fmt;
+
+use crate::fill::is_fill;
+use crate::reader::*;
+
+impl NCFile {
+    /// Renders the file header as CDL text in the style of `ncdump -h`.
+    pub fn to_cdl(&self) -> String {
+        let mut out = String::from("netcdf {\n");
+
+        if !self.dimensions.is_empty() {
+            out.push_str("dimensions:\n");
+            for d in &self.dimensions {
+                if d.length == 0 {
+                    out.push_str(&format!(
+                        "\t{} = UNLIMITED ; // ({} currently)\n", d.name, self.numrecs
+                    ));
+                } else {
+                    out.push_str(&format!("\t{} = {} ;\n", d.name, d.length));
+                }
+            }
+        }
+
+        if !self.variables.is_empty() {
+            out.push_str("variables:\n");
+            for v in &self.variables {
+                out.push_str(&format!("\t{} ;\n", self.cdl_declaration(v)));
+                each_variable!(v, c => {
+                    for a in &c.attributes {
+                        out.push_str(&format!("\t\t{}:{} ;\n", c.name, format_attribute(a)));
+                    }
+                });
+            }
+        }
+
+        if !self.attributes.is_empty() {
+            out.push_str("\n// global attributes:\n");
+            for a in &self.attributes {
+                out.push_str(&format!("\t\t:{} ;\n", format_attribute(a)));
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renders the whole file as CDL text in the style of `ncdump`: the
+    /// header followed by a `data:` section. Fill and missing values are
+    /// written as `_`. If `max_values` is given, longer variables are cut
+    /// short and flagged with a `// truncated` comment.
+    pub fn to_cdl_with_data(&self, max_values: Option<usize>) -> String {
+        let mut out = self.to_cdl();
+        out.truncate(out.len() - "}\n".len());
+
+        if !self.variables.is_empty() {
+            out.push_str("\ndata:\n");
+            for v in &self.variables {
+                let count: usize = self.resolve_shape(v.dimids()).iter().product();
+                let shown = max_values.map_or(count, |max| max.min(count));
+
+                out.push_str(&format!("\n {} = {} ;", v.name(), format_data(v, shown)));
+                if shown < count {
+                    out.push_str(&format!(" // truncated, {} of {} values shown", shown, count));
+                }
+                out.push('\n');
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Parses the header of an ncgen-style CDL document.
+    ///
+    /// Only dimensions, variables and attributes are understood; a `data:`
+    /// section or any other construct is rejected with an error. Variables
+    /// of the resulting file carry no data.
+    pub fn from_cdl(text: &str) -> Result<NCFile> {
+        CdlParser::new(text)?.parse()
+    }
+
+    /// Formats a variable declaration such as `float longitude(longitude)`.
+    pub(crate) fn cdl_declaration(&self, v: &NCVariable) -> String {
+        let (name, dimids) = each_variable!(v, c => (&c.name, &c.dimids));
+        let dims: Vec<&str> = dimids
+            .iter()
+            .map(|&id| match self.dimension_by_id(id) {
+                Some(d) => d.name.as_str(),
+                None => "?",
+            })
+            .collect();
+
+        if dims.is_empty() {
+            format!("{} {}", v.nc_type().name(), name)
+        } else {
+            format!("{} {}({})", v.nc_type().name(), name, dims.join(", "))
+        }
+    }
+}
+
+/// Formats the header as CDL, like `to_cdl`.
+impl fmt::Display for NCFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_cdl())
+    }
+}
+
+impl NCVariable {
+    /// The variable's one-line CDL declaration as printed by `ncdump -h`,
+    /// such as `float longitude(longitude) ;`, naming its dimensions in
+    /// `file`.
+    pub fn summary_line(&self, file: &NCFile) -> String {
+        format!("{} ;", file.cdl_declaration(self))
+    }
+}
+
+/// Formats an attribute as `name = values`.
+fn format_attribute(a: &NCAttribute) -> String {
+    let (name, values) = match a {
+        NCAttribute::Byte(c) => (&c.name, join(&c.values, |v| format!("{}b", v))),
+        NCAttribute::Char(c) => (&c.name, quote(&c.to_string())),
+        NCAttribute::Short(c) => (&c.name, join(&c.values, |v| format!("{}s", v))),
+        NCAttribute::Int(c) => (&c.name, join(&c.values, |v| v.to_string())),
+        NCAttribute::Float(c) => (&c.name, join(&c.values, |v| format!("{}f", format_float(*v)))),
+        NCAttribute::Double(c) => (&c.name, join(&c.values, |v| format_float(*v))),
+        NCAttribute::UByte(c) => (&c.name, join(&c.values, |v| format!("{}ub", v))),
+        NCAttribute::UShort(c) => (&c.name, join(&c.values, |v| format!("{}us", v))),
+        NCAttribute::UInt(c) => (&c.name, join(&c.values, |v| format!("{}u", v))),
+        NCAttribute::Int64(c) => (&c.name, join(&c.values, |v| format!("{}ll", v))),
+        NCAttribute::UInt64(c) => (&c.name, join(&c.values, |v| format!("{}ull", v))),
+    };
+
+    format!("{} = {}", name, values)
+}
+
+/// Formats the first `count` values of a variable's data.
+fn format_data(v: &NCVariable, count: usize) -> String {
+    match v {
+        NCVariable::Char(c) => {
+            let len = count.min(c.data.raw().len());
+            quote(c.decode_text(&c.data.raw()[..len]).trim_end_matches('\0'))
+        }
+        _ => format_values(v, count, "_").unwrap_or_default().join(", "),
+    }
+}
+
+/// Formats the first `count` values of a numeric variable, writing `fill`
+/// in place of fill and missing values. Returns `None` for `Char` data.
+pub(crate) fn format_values(v: &NCVariable, count: usize, fill: &str) -> Option<Vec<String>> {
+    Some(match v {
+        NCVariable::Byte(c) => format_numeric(c, count, fill, |v| v.to_string()),
+        NCVariable::Char(_) => return None,
+        NCVariable::Short(c) => format_numeric(c, count, fill, |v| v.to_string()),
+        NCVariable::Int(c) => format_numeric(c, count, fill, |v| v.to_string()),
+        NCVariable::Float(c) => format_numeric(c, count, fill, |v| format!("{:?}", v)),
+        NCVariable::Double(c) => format_numeric(c, count, fill, format_float),
+        NCVariable::UByte(c) => format_numeric(c, count, fill, |v| v.to_string()),
+        NCVariable::UShort(c) => format_numeric(c, count, fill, |v| v.to_string()),
+        NCVariable::UInt(c) => format_numeric(c, count, fill, |v| v.to_string()),
+        NCVariable::Int64(c) => format_numeric(c, count, fill, |v| v.to_string()),
+        NCVariable::UInt64(c) => format_numeric(c, count, fill, |v| v.to_string()),
+    })
+}
+
+fn format_numeric<T, F>(c: &NCVariableContainer<T>, count: usize, fill: &str, f: F) -> Vec<String>
+where
+    T: NCNumeric,
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+    F: Fn(T) -> String,
+{
+    let fills = c.fill_values();
+
+    c.data
+        .iter()
+        .zip(c.widened())
+        .take(count)
+        .map(|(v, wide)| if is_fill(&fills, wide) { String::from(fill) } else { f(v) })
+        .collect()
+}
+
+fn join<T, F: Fn(&T) -> String>(values: &[T], f: F) -> String {
+    values.iter().map(f).collect::<Vec<String>>().join(", ")
+}
+
+/// Formats a float as the shortest text that reads back as the same value
+/// of its own type, so an `f32` isn't printed with the digits of its `f64`
+/// widening.
+pub(crate) fn format_float<T: Into<f64> + fmt::Debug + Copy>(v: T) -> String {
+    let wide: f64 = v.into();
+    if wide.is_nan() {
+        String::from("NaN")
+    } else if wide.is_infinite() {
+        String::from(if wide > 0.0 { "Infinity" } else { "-Infinity" })
+    } else {
+        format!("{:?}", v)
+    }
+}
+
+pub(crate) fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\0' => out.push_str("\\0"),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Punct(char),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    None,
+    Dimensions,
+    Variables,
+}
+
+enum Number {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Float(f32),
+    Double(f64),
+    UByte(u8),
+    UShort(u16),
+    UInt(u32),
+    Int64(i64),
+    UInt64(u64),
+}
+
+struct CdlParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl CdlParser {
+    fn new(text: &str) -> Result<Self> {
+        Ok(Self {
+            tokens: tokenize(text)?,
+            pos: 0,
+        })
+    }
+
+    fn parse(&mut self) -> Result<NCFile> {
+        let mut f = NCFile {
+            version: 1,
+            numrecs: 0,
+            dimensions: Vec::new(),
+            attributes: Vec::new(),
+            variables: Vec::new(),
+        };
+
+        if self.word()? != "netcdf" {
+            return Err(ParseError::new("CDL must begin with \"netcdf\""));
+        }
+        if self.peek() != Some(&Token::Punct('{')) {
+            self.word()?;
+        }
+        self.punct('{')?;
+
+        let mut section = Section::None;
+        loop {
+            if self.peek() == Some(&Token::Punct('}')) {
+                break;
+            }
+
+            if let Some(next) = self.section_keyword() {
+                section = match next.as_str() {
+                    "dimensions" => Section::Dimensions,
+                    "variables" => Section::Variables,
+                    "data" => return Err(ParseError::new("CDL data sections are not supported")),
+                    other => return Err(ParseError::new(&format!(
+                        "unsupported CDL section \"{}\"", other
+                    ))),
+                };
+                continue;
+            }
+
+            match section {
+                Section::None => return Err(ParseError::new("expected a CDL section")),
+                Section::Dimensions => self.parse_dimensions(&mut f)?,
+                Section::Variables => self.parse_variable_line(&mut f)?,
+            }
+        }
+
+        Ok(f)
+    }
+
+    fn section_keyword(&mut self) -> Option<String> {
+        match (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            (Some(Token::Word(w)), Some(Token::Punct(':')))
+                if ["dimensions", "variables", "data", "types"].contains(&w.as_str()) =>
+            {
+                let w = w.clone();
+                self.pos += 2;
+                Some(w)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_dimensions(&mut self, f: &mut NCFile) -> Result<()> {
+        loop {
+            let name = self.word()?;
+            self.punct('=')?;
+            let value = self.word()?;
+            let length = if value.eq_ignore_ascii_case("unlimited") {
+                0
+            } else {
+                value.parse::<u64>().map_err(|_| ParseError::new(&format!(
+                    "invalid length \"{}\" for dimension {}", value, name
+                )))?
+            };
+            f.dimensions.push(NCDimension { name, length });
+
+            if self.separator()? {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_variable_line(&mut self, f: &mut NCFile) -> Result<()> {
+        if self.peek() == Some(&Token::Punct(':')) {
+            self.pos += 1;
+            let attr = self.parse_attribute()?;
+            f.attributes.push(attr);
+            return Ok(());
+        }
+
+        let first = self.word()?;
+        if self.peek() == Some(&Token::Punct(':')) {
+            self.pos += 1;
+            let attr = self.parse_attribute()?;
+            let var = f
+                .variables
+                .iter_mut()
+                .find(|v| v.has_name(&first))
+                .ok_or_else(|| ParseError::new(&format!(
+                    "attribute on undeclared variable {}", first
+                )))?;
+            each_variable!(var, c => c.attributes.push(attr));
+            return Ok(());
+        }
+
+        loop {
+            let name = self.word()?;
+            let mut dimids = Vec::new();
+
+            if self.peek() == Some(&Token::Punct('(')) {
+                self.pos += 1;
+                loop {
+                    let dim = self.word()?;
+                    let id = f
+                        .dimensions
+                        .iter()
+                        .position(|d| d.name == dim)
+                        .ok_or_else(|| ParseError::new(&format!("unknown dimension {}", dim)))?;
+                    dimids.push(id as u32);
+
+                    match self.next()? {
+                        Token::Punct(',') => continue,
+                        Token::Punct(')') => break,
+                        t => return Err(unexpected(&t)),
+                    }
+                }
+            }
+
+            f.variables.push(new_variable(&first, name, dimids)?);
+
+            if self.separator()? {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_attribute(&mut self) -> Result<NCAttribute> {
+        let name = self.word()?;
+        self.punct('=')?;
+
+        let mut text: Option<String> = None;
+        let mut numbers = Vec::new();
+        loop {
+            match self.next()? {
+                Token::Str(s) => text.get_or_insert_with(String::new).push_str(&s),
+                Token::Word(w) => numbers.push(parse_number(&w)?),
+                t => return Err(unexpected(&t)),
+            }
+
+            if self.separator()? {
+                break;
+            }
+        }
+
+        match text {
+            Some(_) if !numbers.is_empty() => Err(ParseError::new(&format!(
+                "attribute {} mixes text and numbers", name
+            ))),
+            Some(s) => Ok(NCAttribute::Char(NCAttributeContainer::new(&name, s.chars().collect()))),
+            None => numeric_attribute(&name, numbers),
+        }
+    }
+
+    /// Consumes a `,` or `;`, returning true if it was the terminating `;`.
+    fn separator(&mut self) -> Result<bool> {
+        match self.next()? {
+            Token::Punct(',') => Ok(false),
+            Token::Punct(';') => Ok(true),
+            t => Err(unexpected(&t)),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        if self.pos >= self.tokens.len() {
+            return Err(ParseError::new("unexpected end of CDL"));
+        }
+        let t = std::mem::replace(&mut self.tokens[self.pos], Token::Punct(' '));
+        self.pos += 1;
+        Ok(t)
+    }
+
+    fn word(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Word(w) => Ok(w),
+            t => Err(unexpected(&t)),
+        }
+    }
+
+    fn punct(&mut self, c: char) -> Result<()> {
+        match self.next()? {
+            Token::Punct(p) if p == c => Ok(()),
+            t => Err(unexpected(&t)),
+        }
+    }
+}
+
+fn unexpected(t: &Token) -> ParseError {
+    let s = match t {
+        Token::Word(w) => w.clone(),
+        Token::Str(s) => quote(s),
+        Token::Punct(c) => c.to_string(),
+    };
+    ParseError::new(&format!("unexpected \"{}\" in CDL", s))
+}
+
+fn new_variable(ty: &str, name: String, dimids: Vec<u32>) -> Result<NCVariable> {
+    macro_rules! container {
+        () => {
+            NCVariableContainer {
+                name,
+                dimids,
+                attributes: Vec::new(),
+                vsize: 0,
+                offset: 0,
+                data: NCData::new(Vec::new()),
+            }
+        };
+    }
+
+    Ok(match ty {
+        "byte" => NCVariable::Byte(container!()),
+        "char" => NCVariable::Char(container!()),
+        "short" => NCVariable::Short(container!()),
+        "int" | "long" => NCVariable::Int(container!()),
+        "float" | "real" => NCVariable::Float(container!()),
+        "double" => NCVariable::Double(container!()),
+        "ubyte" => NCVariable::UByte(container!()),
+        "ushort" => NCVariable::UShort(container!()),
+        "uint" => NCVariable::UInt(container!()),
+        "int64" => NCVariable::Int64(container!()),
+        "uint64" => NCVariable::UInt64(container!()),
+        other => return Err(ParseError::new(&format!("unsupported CDL type \"{}\"", other))),
+    })
+}
+
+fn parse_number(w: &str) -> Result<Number> {
+    let invalid = || ParseError::new(&format!("invalid number \"{}\" in CDL", w));
+
+    // suffixes of the unsigned and 64-bit integer types, longest first
+    let lower = w.to_ascii_lowercase();
+    let strip = |suffix: &str| lower.ends_with(suffix).then(|| &w[..w.len() - suffix.len()]);
+    if let Some(body) = strip("ull") {
+        return body.parse().map(Number::UInt64).map_err(|_| invalid());
+    }
+    if let Some(body) = strip("ll") {
+        return body.parse().map(Number::Int64).map_err(|_| invalid());
+    }
+    if let Some(body) = strip("ub") {
+        return body.parse().map(Number::UByte).map_err(|_| invalid());
+    }
+    if let Some(body) = strip("us") {
+        return body.parse().map(Number::UShort).map_err(|_| invalid());
+    }
+    if let Some(body) = strip("u") {
+        return body.parse().map(Number::UInt).map_err(|_| invalid());
+    }
+
+    let (body, suffix) = match w.char_indices().last() {
+        Some((i, c)) if "bBsSfFdDlL".contains(c) && !w.ends_with("Inf") => (&w[..i], c.to_ascii_lowercase()),
+        _ => (w, ' '),
+    };
+    let float = |s: &str| -> Result<f64> {
+        match s {
+            "NaN" => Ok(f64::NAN),
+            "Infinity" | "Inf" => Ok(f64::INFINITY),
+            "-Infinity" | "-Inf" => Ok(f64::NEG_INFINITY),
+            _ => s.parse::<f64>().map_err(|_| invalid()),
+        }
+    };
+
+    Ok(match suffix {
+        'b' => Number::Byte(body.parse::<i8>().map_err(|_| invalid())?),
+        's' => Number::Short(body.parse::<i16>().map_err(|_| invalid())?),
+        'l' => Number::Int(body.parse::<i32>().map_err(|_| invalid())?),
+        'f' => Number::Float(float(body)? as f32),
+        'd' => Number::Double(float(body)?),
+        _ if body.contains(['.', 'e', 'E']) || body.contains("NaN") || body.contains("Inf") => {
+            Number::Double(float(body)?)
+        }
+        _ => Number::Int(body.parse::<i32>().map_err(|_| invalid())?),
+    })
+}
+
+fn numeric_attribute(name: &str, numbers: Vec<Number>) -> Result<NCAttribute> {
+    let mixed = || ParseError::new(&format!("attribute {} mixes value types", name));
+
+    macro_rules! collect {
+        ($arm:ident) => {{
+            let values = numbers
+                .into_iter()
+                .map(|n| match n {
+                    Number::$arm(v) => Ok(v),
+                    _ => Err(mixed()),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            NCAttribute::$arm(NCAttributeContainer::new(name, values))
+        }};
+    }
+
+    Ok(match numbers.first() {
+        Some(Number::Byte(_)) => collect!(Byte),
+        Some(Number::Short(_)) => collect!(Short),
+        Some(Number::Int(_)) => collect!(Int),
+        Some(Number::Float(_)) => collect!(Float),
+        Some(Number::Double(_)) => collect!(Double),
+        Some(Number::UByte(_)) => collect!(UByte),
+        Some(Number::UShort(_)) => collect!(UShort),
+        Some(Number::UInt(_)) => collect!(UInt),
+        Some(Number::Int64(_)) => collect!(Int64),
+        Some(Number::UInt64(_)) => collect!(UInt64),
+        None => return Err(ParseError::new(&format!("attribute {} has no values", name))),
+    })
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '/' {
+            chars.next();
+            if chars.next() != Some('/') {
+                return Err(ParseError::new("unexpected \"/\" in CDL"));
+            }
+            while chars.peek().is_some_and(|&c| c != '\n') {
+                chars.next();
+            }
+        } else if c == '"' {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some('n') => s.push('\n'),
+                        Some('t') => s.push('\t'),
+                        Some('0') => s.push('\0'),
+                        Some(c) => s.push(c),
+                        None => return Err(ParseError::new("unterminated string in CDL")),
+                    },
+                    Some(c) => s.push(c),
+                    None => return Err(ParseError::new("unterminated string in CDL")),
+                }
+            }
+            tokens.push(Token::Str(s));
+        } else if "{}(),;=:".contains(c) {
+            chars.next();
+            tokens.push(Token::Punct(c));
+        } else if is_word_char(c) {
+            let mut w = String::new();
+            while let Some(&c) = chars.peek() {
+                if !is_word_char(c) {
+                    break;
+                }
+                w.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(w));
+        } else {
+            return Err(ParseError::new(&format!("unexpected \"{}\" in CDL", c)));
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || "_.-+@".contains(c)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    const MINIMAL: &str = r#"
+        netcdf minimal {
+        dimensions:
+            time = UNLIMITED ; // (0 currently)
+            lon = 3 ;
+        variables:
+            double time(time) ;
+                time:units = "hours since 1900-01-01" ;
+            float temp(time, lon) ;
+                temp:_FillValue = -999.f ;
+                temp:valid_range = 0s, 100s ;
+
+        // global attributes:
+                :Conventions = "CF-1.6" ;
+        }
+    "#;
+
+    #[test]
+    fn it_renders_the_data_section() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let cdl = f.to_cdl_with_data(Some(3));
+
+        assert!(cdl.starts_with(&f.to_cdl()[..f.to_cdl().len() - 2]));
+        assert!(cdl.contains(
+            "\ndata:\n\n longitude = -24.95, -24.85, -24.75 ; // truncated, 3 of 700 values shown\n"
+        ));
+        assert!(cdl.contains("\n latitude = 69.95, 69.85, 69.75 ;"));
+        assert!(cdl.ends_with(" ; // truncated, 3 of 7000000 values shown\n}\n"));
+
+        let v = NCVariableContainer::<i16> {
+            name: String::from("s"),
+            dimids: vec![0],
+            attributes: vec![NCAttribute::Short(NCAttributeContainer::new("_FillValue", vec![-1]))],
+            vsize: 8,
+            offset: 0,
+            data: NCData::new(vec![0, 1, 0xff, 0xff, 0, 3, 0, 0]),
+        };
+        let mut f = NCFile::from_cdl("netcdf { dimensions: x = 3 ; }").unwrap();
+        f.variables.push(NCVariable::Short(v));
+        assert!(f.to_cdl_with_data(None).ends_with("data:\n\n s = 1, _, 3 ;\n}\n"));
+
+        f.variables[0] = NCVariable::Char(NCVariableContainer {
+            name: String::from("c"),
+            dimids: vec![0],
+            attributes: Vec::new(),
+            vsize: 4,
+            offset: 0,
+            data: NCData::new(b"ab\0\0".to_vec()),
+        });
+        assert!(f.to_cdl_with_data(None).ends_with(" c = \"ab\" ;\n}\n"));
+    }
+
+    #[test]
+    fn it_displays_files_as_cdl() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let shown = f.to_string();
+
+        assert_eq!(shown, f.to_cdl());
+        assert!(shown.contains("\tlongitude = 700 ;\n"));
+        assert!(shown.contains("\tfloat longitude(longitude) ;\n"));
+        assert!(shown.contains("\t\t:Conventions = \"CF-1.6\" ;\n"));
+        assert!(!shown.contains("data:"));
+    }
+
+    #[test]
+    fn it_formats_floats_at_their_own_precision() {
+        let f = NCFile::from_cdl("netcdf { variables: :scale = 0.01f ; :offset = 0.1 ; }").unwrap();
+        let cdl = f.to_cdl();
+
+        assert!(cdl.contains("\t\t:scale = 0.01f ;\n"), "{}", cdl);
+        assert!(cdl.contains("\t\t:offset = 0.1 ;\n"), "{}", cdl);
+    }
+
+    #[test]
+    fn it_parses_cdl_headers() {
+        let f = NCFile::from_cdl(MINIMAL).unwrap();
+
+        assert_eq!(f.dimensions.len(), 2);
+        assert_eq!(f.dimensions[0].name, "time");
+        assert_eq!(f.dimensions[0].length, 0);
+        assert_eq!(f.dimensions[1].length, 3);
+
+        if let NCVariable::Float(v) = &f.variables[1] {
+            assert_eq!(v.name, "temp");
+            assert_eq!(v.dimids, vec![0, 1]);
+            if let NCAttribute::Short(a) = &v.attributes[1] {
+                assert_eq!(a.values, vec![0, 100]);
+            } else {
+                panic!("valid_range isn't Short");
+            }
+        } else {
+            panic!("second variable isn't Float");
+        }
+
+        if let NCAttribute::Char(c) = &f.attributes[0] {
+            assert_eq!(c.to_string(), "CF-1.6");
+        } else {
+            panic!("global attribute isn't Char");
+        }
+    }
+
+    #[test]
+    fn it_rejects_unsupported_cdl() {
+        let data = MINIMAL.replace("        }\n    ", "data:\n lon = 1, 2, 3 ;\n}");
+        assert!(NCFile::from_cdl(&data).is_err());
+        assert!(NCFile::from_cdl(&MINIMAL.replace("double", "string")).is_err());
+        assert!(NCFile::from_cdl(&MINIMAL.replace("(time, lon)", "(lat)")).is_err());
+    }
+
+    #[test]
+    fn it_round_trips_cdl() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let cdl = f.to_cdl();
+
+        assert!(cdl.contains("\tfloat longitude(longitude) ;\n"));
+        assert!(cdl.contains("\t\t:Conventions = \"CF-1.6\" ;\n"));
+        assert!(cdl.contains("\t\tunknown:_FillValue = -32767s ;\n"));
+
+        let g = NCFile::from_cdl(&cdl).unwrap();
+        assert_eq!(g.to_cdl(), cdl);
+    }
+
+    #[test]
+    fn it_round_trips_cdf5_types() {
+        let cdl = MINIMAL
+            .replace("double time", "uint64 time")
+            .replace("-999.f", "255ub")
+            .replace("0s, 100s", "0us, 65535us")
+            .replace(":Conventions = \"CF-1.6\"", ":ids = 4294967295u, 5u");
+        let f = NCFile::from_cdl(&cdl).unwrap();
+
+        assert_eq!(f.variables[0].nc_type(), NCType::UInt64);
+        let temp = f.variable("temp").unwrap();
+        assert_eq!(temp.attributes()[0].nc_type(), NCType::UByte);
+        assert_eq!(temp.attributes()[1].nc_type(), NCType::UShort);
+        assert_eq!(f.attribute("ids").unwrap().nc_type(), NCType::UInt);
+        assert!(f.to_cdl().contains("\t\ttemp:valid_range = 0us, 65535us ;\n"));
+        assert_eq!(NCFile::from_cdl(&f.to_cdl()).unwrap().to_cdl(), f.to_cdl());
+
+        assert!(NCFile::from_cdl(&cdl.replace(", 5u", ", -5ll")).is_err());
+        assert!(NCFile::from_cdl(&cdl.replace("255ub", "256ub")).is_err());
+    }
+
+    #[test]
+    fn it_renders_summary_lines() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+
+        let lon = f.variable("longitude").unwrap();
+        assert_eq!(lon.summary_line(&f), "float longitude(longitude) ;");
+        let unknown = f.variable("unknown").unwrap();
+        assert_eq!(unknown.summary_line(&f), "short unknown(time, latitude, longitude) ;");
+    }
+}
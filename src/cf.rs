@@ -0,0 +1,250 @@
+use crate::reader::*;
+
+/// Descriptive global attributes recommended by the CF conventions.
+#[derive(Debug, Default, PartialEq)]
+pub struct FileMetadata {
+    pub title: Option<String>,
+    pub institution: Option<String>,
+    pub source: Option<String>,
+    pub history: Option<String>,
+    pub references: Option<String>,
+    pub comment: Option<String>,
+    pub conventions: Option<String>,
+}
+
+impl NCFile {
+    /// Gathers the common descriptive global attributes. Attributes that are
+    /// absent or aren't text are left as `None`.
+    pub fn metadata(&self) -> FileMetadata {
+        let text = |name| self.attribute(name).and_then(|a| a.as_text());
+
+        FileMetadata {
+            title: text("title"),
+            institution: text("institution"),
+            source: text("source"),
+            history: text("history"),
+            references: text("references"),
+            comment: text("comment"),
+            conventions: text("Conventions"),
+        }
+    }
+
+    /// Whether the file declares CF conventions, i.e. its global
+    /// `Conventions` attribute mentions a `CF-` version.
+    pub fn is_cf(&self) -> bool {
+        self.attribute("Conventions")
+            .and_then(|a| a.as_text())
+            .is_some_and(|c| c.contains("CF-"))
+    }
+
+    /// The discrete sampling geometry declared by the global `featureType`
+    /// attribute, such as `timeSeries`, `profile` or `trajectory`.
+    pub fn feature_type(&self) -> Option<String> {
+        self.attribute("featureType").and_then(|a| a.as_text())
+    }
+
+    /// The `_NCProperties` global attribute recording the version of the
+    /// library that wrote the file.
+    pub fn nc_properties(&self) -> Option<String> {
+        self.attribute("_NCProperties").and_then(|a| a.as_text())
+    }
+
+    /// The global attributes meant for users, leaving out reserved ones
+    /// whose names start with an underscore, such as `_NCProperties`.
+    pub fn public_global_attributes(&self) -> Vec<&NCAttribute> {
+        self.attributes
+            .iter()
+            .filter(|a| !a.name().starts_with('_'))
+            .collect()
+    }
+
+    /// The variables whose text attribute `attr_name` equals `attr_value`.
+    pub fn variables_with_attr(&self, attr_name: &str, attr_value: &str) -> Vec<&NCVariable> {
+        self.variables
+            .iter()
+            .filter(|v| {
+                v.attributes()
+                    .iter()
+                    .any(|a| a.has_name(attr_name) && a.as_text().is_some_and(|t| t == attr_value))
+            })
+            .collect()
+    }
+
+    /// Names of the attributes defined on every variable, in the order they
+    /// appear on the first variable.
+    pub fn common_variable_attributes(&self) -> Vec<String> {
+        let (first, rest) = match self.variables.split_first() {
+            Some(split) => split,
+            None => return Vec::new(),
+        };
+
+        first
+            .attributes()
+            .iter()
+            .map(|a| a.name())
+            .filter(|&name| rest.iter().all(|v| v.attributes().iter().any(|a| a.has_name(name))))
+            .map(String::from)
+            .collect()
+    }
+}
+
+impl NCVariable {
+    /// Pairs the CF `flag_values` of a categorical variable with the
+    /// space-separated names in `flag_meanings`. Returns `None` if either
+    /// attribute is missing or their lengths disagree.
+    pub fn flag_map(&self) -> Option<Vec<(f64, String)>> {
+        let attr = |name| self.attributes().iter().find(|a| a.has_name(name));
+        let values = attr("flag_values")?.to_f64_vec()?;
+        let meanings = attr("flag_meanings")?.as_text()?;
+        let meanings: Vec<&str> = meanings.split_whitespace().collect();
+
+        if values.len() != meanings.len() {
+            return None;
+        }
+
+        Some(values.into_iter().zip(meanings.into_iter().map(String::from)).collect())
+    }
+
+    /// The grid mapping variable in `file` named by this variable's CF
+    /// `grid_mapping` attribute, describing its map projection.
+    pub fn grid_mapping<'a>(&self, file: &'a NCFile) -> Option<&'a NCVariable> {
+        let name = self.attributes().iter().find(|a| a.has_name("grid_mapping"))?.as_text()?;
+
+        file.variable(name.trim())
+    }
+
+    /// The auxiliary coordinate variables in `file` listed in this variable's
+    /// space-separated `coordinates` attribute. Names that don't resolve to a
+    /// variable are skipped.
+    pub fn aux_coordinates<'a>(&self, file: &'a NCFile) -> Vec<&'a NCVariable> {
+        let names = match self.attributes().iter().find(|a| a.has_name("coordinates")) {
+            Some(a) => a.as_text().unwrap_or_default(),
+            None => return Vec::new(),
+        };
+
+        names.split_whitespace().filter_map(|name| file.variable(name)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    #[test]
+    fn it_reads_file_metadata() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let m = f.metadata();
+
+        assert_eq!(m.conventions.as_deref(), Some("CF-1.6"));
+        assert!(m.history.unwrap().contains("grib_to_netcdf"));
+        assert_eq!(m.title, None);
+        assert_eq!(m.institution, None);
+    }
+
+    #[test]
+    fn it_lists_common_variable_attributes() {
+        let mut f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        assert!(f.common_variable_attributes().is_empty());
+
+        // every variable but the packed one carries units
+        f.variables.pop();
+        assert_eq!(f.common_variable_attributes(), vec!["units", "long_name"]);
+    }
+
+    #[test]
+    fn it_selects_variables_by_attribute_value() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+
+        let vars = f.variables_with_attr("units", "degrees_east");
+        assert_eq!(vars.len(), 1);
+        assert_eq!(vars[0].name(), "longitude");
+        assert!(f.variables_with_attr("units", "degrees").is_empty());
+    }
+
+    #[test]
+    fn it_reads_the_feature_type() {
+        let mut f = file(Vec::new(), Vec::new());
+        assert_eq!(f.feature_type(), None);
+
+        f.attributes.push(char_attr("featureType", "timeSeries"));
+        assert_eq!(f.feature_type().as_deref(), Some("timeSeries"));
+    }
+
+    #[test]
+    fn it_reads_nc_properties() {
+        let mut f = file(Vec::new(), Vec::new());
+        assert_eq!(f.nc_properties(), None);
+
+        f.attributes.push(char_attr("_NCProperties", "version=2,netcdf=4.9.2"));
+        assert_eq!(f.nc_properties().as_deref(), Some("version=2,netcdf=4.9.2"));
+    }
+
+    #[test]
+    fn it_resolves_auxiliary_coordinates() {
+        let mut temp = float_container("temp", &[], &[]);
+        temp.attributes.push(char_attr("coordinates", "lat lon height"));
+        let f = file(
+            Vec::new(),
+            vec![NCVariable::Float(temp), float_var("lon", &[], &[]), float_var("lat", &[], &[])],
+        );
+
+        let coords = f.variables[0].aux_coordinates(&f);
+        assert_eq!(coords.len(), 2);
+        assert!(coords[0].has_name("lat"));
+        assert!(coords[1].has_name("lon"));
+        assert!(f.variables[1].aux_coordinates(&f).is_empty());
+    }
+
+    #[test]
+    fn it_skips_reserved_global_attributes() {
+        let mut f = file(Vec::new(), Vec::new());
+        f.attributes.push(char_attr("_NCProperties", "version=2"));
+        f.attributes.push(char_attr("title", "Surface temperature"));
+        f.attributes.push(float_attr("_FillValue", &[0.0]));
+
+        let public = f.public_global_attributes();
+        assert_eq!(public.len(), 1);
+        assert!(public[0].has_name("title"));
+    }
+
+    #[test]
+    fn it_detects_cf_conventions() {
+        assert!(NCFile::open(SAMPLE_FILE_1).unwrap().is_cf());
+
+        let mut f = file(Vec::new(), Vec::new());
+        assert!(!f.is_cf());
+
+        f.attributes.push(char_attr("Conventions", "COARDS"));
+        assert!(!f.is_cf());
+    }
+
+    #[test]
+    fn it_maps_flag_values_to_meanings() {
+        let mut v = float_container("quality", &[], &[]);
+        v.attributes.push(float_attr("flag_values", &[0.0, 1.0, 2.0]));
+        v.attributes.push(char_attr("flag_meanings", "good  suspect bad"));
+
+        let flags = NCVariable::Float(v.clone()).flag_map().unwrap();
+        assert_eq!(
+            flags,
+            vec![(0.0, "good".to_string()), (1.0, "suspect".to_string()), (2.0, "bad".to_string())]
+        );
+
+        v.attributes[1] = char_attr("flag_meanings", "good bad");
+        assert_eq!(NCVariable::Float(v).flag_map(), None);
+    }
+
+    #[test]
+    fn it_resolves_the_grid_mapping() {
+        let mut temp = float_container("temp", &[], &[]);
+        temp.attributes.push(char_attr("grid_mapping", "crs"));
+        let f = file(Vec::new(), vec![NCVariable::Float(temp), float_var("crs", &[], &[])]);
+
+        let crs = f.variables[0].grid_mapping(&f).unwrap();
+        assert!(crs.has_name("crs"));
+        assert!(f.variables[1].grid_mapping(&f).is_none());
+    }
+}
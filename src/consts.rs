@@ -1,4 +1,5 @@
-pub const MAGIC_NUMBER: &'static str = "CDF";
+pub const MAGIC_NUMBER: &str = "CDF";
+pub const HDF5_SIGNATURE: &[u8] = b"\x89HDF\r\n\x1a\n";
 
 pub const ZERO: u8         = 0x00000000;
 pub const NC_DIMENSION: u8 = 0x0000000a;
@@ -11,6 +12,11 @@ pub const NC_SHORT: u8     = 0x00000003;
 pub const NC_INT: u8       = 0x00000004;
 pub const NC_FLOAT: u8     = 0x00000005;
 pub const NC_DOUBLE: u8    = 0x00000006;
+pub const NC_UBYTE: u8     = 0x00000007;
+pub const NC_USHORT: u8    = 0x00000008;
+pub const NC_UINT: u8      = 0x00000009;
+pub const NC_INT64: u8     = 0x0000000a;
+pub const NC_UINT64: u8    = 0x0000000b;
 
 pub const FILL_CHAR: u8    = 0x00;
 pub const FILL_BYTE: u8    = 0x81;
@@ -18,5 +24,26 @@ pub const FILL_SHORT: u16  = 0x8001;
 pub const FILL_INT: u32    = 0x80000001;
 pub const FILL_FLOAT: u32  = 0x7cf00000;
 pub const FILL_DOUBLE: u64 = 0x479e000000000000;
+pub const FILL_UBYTE: u8   = 0xff;
+pub const FILL_USHORT: u16 = 0xffff;
+pub const FILL_UINT: u32   = 0xffffffff;
+pub const FILL_INT64: u64  = 0x8000000000000002;
+pub const FILL_UINT64: u64 = 0xfffffffffffffffe;
+
+/// The default fill values reinterpreted as the types they fill.
+pub const DEFAULT_FILL_CHAR: char   = FILL_CHAR as char;
+pub const DEFAULT_FILL_BYTE: i8     = FILL_BYTE as i8;
+pub const DEFAULT_FILL_SHORT: i16   = FILL_SHORT as i16;
+pub const DEFAULT_FILL_INT: i32     = FILL_INT as i32;
+pub const DEFAULT_FILL_FLOAT: f32   = f32::from_bits(FILL_FLOAT);
+pub const DEFAULT_FILL_DOUBLE: f64  = f64::from_bits(FILL_DOUBLE);
+pub const DEFAULT_FILL_UBYTE: u8    = FILL_UBYTE;
+pub const DEFAULT_FILL_USHORT: u16  = FILL_USHORT;
+pub const DEFAULT_FILL_UINT: u32    = FILL_UINT;
+pub const DEFAULT_FILL_INT64: i64   = FILL_INT64 as i64;
+pub const DEFAULT_FILL_UINT64: u64  = FILL_UINT64;
 
 pub const STREAMING: u32 = 0xffffffff;
+pub const STREAMING_64: u64 = 0xffffffffffffffff;
+
+pub const VERSION_CDF5: u8 = 0x05;
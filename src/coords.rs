@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use crate::cdl::format_values;
+use crate::reader::*;
+
+/// The direction in which a variable's values strictly change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Monotonicity {
+    Increasing,
+    Decreasing,
+}
+
+impl<T: NCNumeric> NCVariableContainer<T>
+where
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+{
+    /// Whether the values, widened to `f64`, strictly increase or strictly
+    /// decrease, as coordinate values should. Returns `None` if they do
+    /// neither, including when two neighbours are equal or NaN, or when
+    /// there are fewer than two values.
+    pub fn is_monotonic(&self) -> Option<Monotonicity> {
+        let values: Vec<f64> = self.widened().collect();
+        let mut steps = values.windows(2).map(|w| w[1] - w[0]);
+
+        let direction = match steps.next()? {
+            d if d > 0.0 => Monotonicity::Increasing,
+            d if d < 0.0 => Monotonicity::Decreasing,
+            _ => return None,
+        };
+        let strict = match direction {
+            Monotonicity::Increasing => steps.all(|d| d > 0.0),
+            Monotonicity::Decreasing => steps.all(|d| d < 0.0),
+        };
+
+        strict.then_some(direction)
+    }
+}
+
+/// The decoded values of a file's coordinate variables, built once by
+/// `NCFile::coordinate_cache` for repeated lookups. It borrows the file, so
+/// the file can't change while the cache is alive.
+#[derive(Debug, Clone)]
+pub struct CoordinateCache<'a> {
+    values: HashMap<&'a str, Vec<f64>>,
+}
+
+impl CoordinateCache<'_> {
+    /// The values of the coordinate variable `name`, or `None` if the file
+    /// has no such coordinate variable or it isn't numeric.
+    pub fn values(&self, name: &str) -> Option<&[f64]> {
+        self.values.get(name).map(Vec::as_slice)
+    }
+}
+
+impl NCFile {
+    /// The coordinate variable of the dimension `dim`: a one-dimensional
+    /// variable sharing the dimension's name.
+    pub(crate) fn coordinate_variable(&self, dim: &str) -> Option<&NCVariable> {
+        let id = self.dimensions.iter().position(|d| d.name == dim)? as u32;
+
+        self.variable(dim).filter(|v| v.dimids() == [id])
+    }
+
+    /// Decodes the values of every coordinate variable, widened to `f64`
+    /// and trimmed to the dimension's length, into a cache for repeated
+    /// lookups.
+    pub fn coordinate_cache(&self) -> CoordinateCache<'_> {
+        let values = self
+            .dimensions
+            .iter()
+            .enumerate()
+            .filter_map(|(id, d)| {
+                let mut values = self.coordinate_variable(&d.name)?.to_f64_vec()?;
+                values.truncate(self.resolve_shape(&[id as u32])[0]);
+                Some((d.name.as_str(), values))
+            })
+            .collect();
+
+        CoordinateCache { values }
+    }
+
+    /// Pairs the values of the one-dimensional variable `var_name` with its
+    /// time coordinate, as `(time, value)`. Returns `None` if the variable
+    /// isn't one-dimensional or its dimension isn't a time axis, i.e. has no
+    /// coordinate variable with `axis = "T"` or units of the form
+    /// `<unit> since <epoch>`.
+    pub fn time_series(&self, var_name: &str) -> Option<Vec<(f64, f64)>> {
+        let var = self.variable(var_name)?;
+        let dim = match var.dimids() {
+            [id] => self.dimension_by_id(*id)?,
+            _ => return None,
+        };
+
+        let time = self.coordinate_variable(&dim.name)?;
+        let text = |name| time.attributes().iter().find(|a| a.has_name(name))?.as_text();
+        let is_time = text("axis").is_some_and(|a| a == "T")
+            || text("units").is_some_and(|u| u.contains(" since "));
+        if !is_time {
+            return None;
+        }
+
+        Some(time.to_f64_vec()?.into_iter().zip(var.to_f64_vec()?).collect())
+    }
+
+    /// Renders the coordinate variables as CSV, one column per variable in
+    /// dimension order under a header row of their names. Shorter columns
+    /// are padded with blank cells, as are fill values.
+    pub fn coordinates_table(&self) -> String {
+        let mut names = Vec::new();
+        let mut columns = Vec::new();
+        for d in &self.dimensions {
+            if let Some(v) = self.coordinate_variable(&d.name) {
+                let count: usize = self.resolve_shape(v.dimids()).iter().product();
+                if let Some(values) = format_values(v, count, "") {
+                    names.push(csv_field(&d.name));
+                    columns.push(values);
+                }
+            }
+        }
+
+        let mut out = names.join(",");
+        out.push('\n');
+
+        let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
+        for i in 0..rows {
+            let row: Vec<&str> = columns
+                .iter()
+                .map(|c| c.get(i).map_or("", String::as_str))
+                .collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// Expands the coordinate variables of `lon_dim` and `lat_dim` into every
+    /// `(lon, lat)` grid point, in row-major order with longitude varying
+    /// fastest.
+    pub fn grid_points(&self, lon_dim: &str, lat_dim: &str) -> Option<Vec<(f64, f64)>> {
+        let lon = self.coordinate_variable(lon_dim)?.to_f64_vec()?;
+        let lat = self.coordinate_variable(lat_dim)?.to_f64_vec()?;
+
+        Some(
+            lat.iter()
+                .flat_map(|&y| lon.iter().map(move |&x| (x, y)))
+                .collect(),
+        )
+    }
+
+    /// The spacing between consecutive values of the coordinate variable
+    /// `coord_name`, as a positive number. Returns `None` if there are fewer
+    /// than two values or any step differs from the mean step by more than
+    /// a thousandth of it.
+    pub fn coordinate_resolution(&self, coord_name: &str) -> Option<f64> {
+        let var = self.coordinate_variable(coord_name)?;
+        let mut values = var.to_f64_vec()?;
+        values.truncate(self.resolve_shape(var.dimids())[0]);
+        if values.len() < 2 {
+            return None;
+        }
+
+        let mean = (values[values.len() - 1] - values[0]) / (values.len() - 1) as f64;
+        let tolerance = mean.abs() * 1e-3;
+        let uniform = values.windows(2).all(|w| (w[1] - w[0] - mean).abs() <= tolerance);
+
+        (uniform && mean != 0.0).then_some(mean.abs())
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        String::from(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::coords::Monotonicity;
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn it_expands_grid_points() {
+        let f = file(
+            vec![dim("lon", 3), dim("lat", 2)],
+            vec![
+                float_var("lon", &[0], &[10.0, 20.0, 30.0]),
+                float_var("lat", &[1], &[-5.0, 5.0]),
+            ],
+        );
+
+        let points = f.grid_points("lon", "lat").unwrap();
+        assert_eq!(points.len(), 3 * 2);
+        assert_eq!(points[0], (10.0, -5.0));
+        assert_eq!(points[2], (30.0, -5.0));
+        assert_eq!(points[3], (10.0, 5.0));
+
+        assert_eq!(f.grid_points("lon", "time"), None);
+    }
+
+    #[test]
+    fn it_pairs_values_with_time() {
+        let mut time = float_container("time", &[0], &[0.0, 6.0, 12.0]);
+        time.attributes.push(char_attr("units", "hours since 2000-01-01"));
+        let mut f = file(
+            vec![dim("time", 3), dim("lon", 1)],
+            vec![
+                NCVariable::Float(time),
+                float_var("temp", &[0], &[280.5, 281.0, 279.5]),
+                float_var("lon", &[1], &[0.0]),
+            ],
+        );
+
+        let series = f.time_series("temp").unwrap();
+        assert_eq!(series, vec![(0.0, 280.5), (6.0, 281.0), (12.0, 279.5)]);
+        assert_eq!(f.time_series("lon"), None);
+
+        f.variables[0].attributes_mut().clear();
+        assert_eq!(f.time_series("temp"), None);
+    }
+
+    #[test]
+    fn it_tabulates_coordinates() {
+        let table = NCFile::open("./samples/sample1.nc").unwrap().coordinates_table();
+        let mut lines = table.lines();
+
+        assert_eq!(lines.next(), Some("longitude,latitude,time"));
+        assert!(lines.next().unwrap().starts_with("-24.95,69.95,"));
+        assert_eq!(table.lines().count(), 1 + 700);
+        assert!(table.lines().nth(500).unwrap().ends_with(",,"));
+
+        let f = file(
+            vec![dim("x", 2), dim("y,z", 1)],
+            vec![float_var("x", &[0], &[1.0, 2.0]), float_var("y,z", &[1], &[5.0])],
+        );
+        assert_eq!(f.coordinates_table(), "x,\"y,z\"\n1.0,5.0\n2.0,\n");
+    }
+
+    #[test]
+    fn it_detects_monotonic_coordinates() {
+        let f = NCFile::open("./samples/sample1.nc").unwrap();
+        match f.variable("longitude").unwrap() {
+            NCVariable::Float(lon) => assert_eq!(lon.is_monotonic(), Some(Monotonicity::Increasing)),
+            _ => panic!("longitude isn't Float"),
+        }
+
+        let v = float_container("x", &[0], &[3.0, 2.0, -1.0]);
+        assert_eq!(v.is_monotonic(), Some(Monotonicity::Decreasing));
+        let v = float_container("x", &[0], &[1.0, 2.0, 2.0]);
+        assert_eq!(v.is_monotonic(), None);
+        let v = float_container("x", &[0], &[1.0, 3.0, 2.0]);
+        assert_eq!(v.is_monotonic(), None);
+        let v = float_container("x", &[0], &[1.0]);
+        assert_eq!(v.is_monotonic(), None);
+    }
+
+    #[test]
+    fn it_detects_coordinate_resolution() {
+        let f = NCFile::open("./samples/sample1.nc").unwrap();
+        let resolution = f.coordinate_resolution("longitude").unwrap();
+        assert!((resolution - 0.1).abs() < 1e-6);
+        assert_eq!(f.coordinate_resolution("unknown"), None);
+
+        let f = file(
+            vec![dim("x", 3), dim("y", 3), dim("z", 1)],
+            vec![
+                float_var("x", &[0], &[4.0, 2.0, 0.0]),
+                float_var("y", &[1], &[0.0, 1.0, 3.0]),
+                float_var("z", &[2], &[1.0]),
+            ],
+        );
+        assert_eq!(f.coordinate_resolution("x"), Some(2.0));
+        assert_eq!(f.coordinate_resolution("y"), None);
+        assert_eq!(f.coordinate_resolution("z"), None);
+    }
+
+    #[test]
+    fn it_caches_coordinate_values() {
+        let f = NCFile::open("./samples/sample1.nc").unwrap();
+        let cache = f.coordinate_cache();
+
+        let fresh = f.variable("longitude").unwrap().to_f64_vec().unwrap();
+        assert_eq!(cache.values("longitude"), Some(&fresh[..]));
+        assert_eq!(cache.values("time").unwrap().len(), 25);
+        assert_eq!(cache.values("unknown"), None);
+    }
+}
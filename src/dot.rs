@@ -0,0 +1,58 @@
+use crate::cdl::quote;
+use crate::reader::*;
+
+impl NCFile {
+    /// Renders the file's schema as a GraphViz `digraph`: dimensions are box
+    /// nodes, variables are ellipse nodes, and each variable has an edge to
+    /// every dimension it spans, labelled with the axis position.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph netcdf {\n");
+
+        for d in &self.dimensions {
+            let label = if d.length == 0 {
+                format!("{} = UNLIMITED ({} currently)", d.name, self.numrecs)
+            } else {
+                format!("{} = {}", d.name, d.length)
+            };
+            out.push_str(&format!(
+                "\t{} [shape=box, label={}];\n", quote(&format!("dim/{}", d.name)), quote(&label)
+            ));
+        }
+
+        for v in &self.variables {
+            each_variable!(v, c => {
+                let id = quote(&format!("var/{}", c.name));
+                let label = format!("{} {}", v.nc_type().name(), c.name);
+                out.push_str(&format!("\t{} [shape=ellipse, label={}];\n", id, quote(&label)));
+
+                for (axis, name) in self.dimension_names(v).iter().enumerate() {
+                    out.push_str(&format!(
+                        "\t{} -> {} [label=\"{}\"];\n", id, quote(&format!("dim/{}", name)), axis
+                    ));
+                }
+            });
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    #[test]
+    fn it_renders_dot() {
+        let dot = NCFile::open(SAMPLE_FILE_1).unwrap().to_dot();
+
+        assert!(dot.starts_with("digraph netcdf {\n"));
+        assert!(dot.contains("\t\"dim/longitude\" [shape=box, label=\"longitude = 700\"];\n"));
+        assert!(dot.contains("\t\"var/longitude\" [shape=ellipse, label=\"float longitude\"];\n"));
+        assert!(dot.contains("\t\"var/longitude\" -> \"dim/longitude\" [label=\"0\"];\n"));
+        assert!(dot.contains("\t\"var/unknown\" -> \"dim/longitude\" [label=\"2\"];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
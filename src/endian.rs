@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use crate::reader::*;
+
+/// The byte order of stored values. The format mandates big-endian data,
+/// but broken writers sometimes produce little-endian data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+impl NCFile {
+    /// Guesses the byte order of the data in the file at `path` by decoding
+    /// the first floating point variable both ways and picking whichever
+    /// yields more finite, plausibly scaled values.
+    ///
+    /// This is a best-effort diagnostic only. When nothing can be inferred,
+    /// such as when the file can't be read or has no floating point
+    /// variables, `Endian::Big` is returned as mandated by the format. Only
+    /// the header and the data of that one variable are read.
+    pub fn guess_endianness<P: AsRef<Path>>(path: P) -> Endian {
+        let f = match NCFile::open_lazy(path) {
+            Ok(f) => f,
+            Err(_) => return Endian::Big,
+        };
+
+        let first = f.variables.iter().find_map(|v| match v {
+            NCVariable::Float(c) => Some((c.data.raw(), 4)),
+            NCVariable::Double(c) => Some((c.data.raw(), 8)),
+            _ => None,
+        });
+
+        match first {
+            Some((raw, size)) => guess_from_raw(raw, size),
+            None => Endian::Big,
+        }
+    }
+}
+
+fn guess_from_raw(raw: &[u8], size: usize) -> Endian {
+    let mut big = 0;
+    let mut little = 0;
+
+    for chunk in raw.chunks_exact(size) {
+        let (be, le) = if size == 4 {
+            let buf = [chunk[0], chunk[1], chunk[2], chunk[3]];
+            (f64::from(f32::from_be_bytes(buf)), f64::from(f32::from_le_bytes(buf)))
+        } else {
+            let buf = [chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7]];
+            (f64::from_be_bytes(buf), f64::from_le_bytes(buf))
+        };
+
+        big += plausible(be) as usize;
+        little += plausible(le) as usize;
+    }
+
+    if little > big {
+        Endian::Little
+    } else {
+        Endian::Big
+    }
+}
+
+fn plausible(v: f64) -> bool {
+    v == 0.0 || (v.is_finite() && v.abs() > 1e-30 && v.abs() < 1e30)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    #[test]
+    fn it_guesses_big_endian_samples() {
+        assert_eq!(NCFile::guess_endianness(SAMPLE_FILE_1), Endian::Big);
+    }
+
+    #[test]
+    fn it_guesses_little_endian_data() {
+        let raw: Vec<u8> = [-24.95f32, -24.85, -24.75, 12.5]
+            .iter()
+            .flat_map(|v| v.to_le_bytes().to_vec())
+            .collect();
+
+        assert_eq!(guess_from_raw(&raw, 4), Endian::Little);
+    }
+}
@@ -0,0 +1,382 @@
+use crate::reader::*;
+
+impl<T: NCNumeric> NCVariableContainer<T>
+where
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+{
+    /// Whether the variable carries `_Unsigned = "true"`, meaning its signed
+    /// integer storage should be read as the unsigned counterpart.
+    pub fn is_unsigned(&self) -> bool {
+        self.attribute("_Unsigned")
+            .and_then(|a| a.as_text())
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+    }
+
+    /// Decodes the data widened to `f64`, honouring `_Unsigned`.
+    pub(crate) fn widened(&self) -> impl Iterator<Item = f64> + '_ {
+        let unsigned = self.is_unsigned();
+
+        self.data.iter().map(move |v| {
+            if unsigned {
+                T::reinterpret_unsigned(v.to_f64())
+            } else {
+                v.to_f64()
+            }
+        })
+    }
+
+    /// The values marking missing data in this variable: its `_FillValue`
+    /// (or the type's default fill if there is none) plus any
+    /// `missing_value`s, widened to `f64` the same way as the data.
+    pub(crate) fn fill_values(&self) -> Vec<f64> {
+        let mut fills = match self.attribute("_FillValue").and_then(|a| a.to_f64_vec()) {
+            Some(v) => v,
+            None => vec![T::default_fill().to_f64()],
+        };
+
+        if let Some(v) = self.attribute("missing_value").and_then(|a| a.to_f64_vec()) {
+            fills.extend(v);
+        }
+
+        if self.is_unsigned() {
+            fills = fills.into_iter().map(T::reinterpret_unsigned).collect();
+        }
+
+        fills
+    }
+
+    /// Decodes the data widened to `f64`, substituting `f64::NAN` for every
+    /// fill or missing value.
+    pub fn to_f64_with_nan_fill(&self) -> Vec<f64> {
+        let fills = self.fill_values();
+
+        self.widened()
+            .map(|v| if is_fill(&fills, v) { f64::NAN } else { v })
+            .collect()
+    }
+
+    /// Iterates over the data, yielding `None` in place of every value equal
+    /// to the `_FillValue` or a `missing_value`. Without a `_FillValue`
+    /// attribute, the type's default fill value is masked instead.
+    pub fn iter_masked(&self) -> impl Iterator<Item = Option<T>> + '_ {
+        let fills = self.fill_values();
+
+        self.data
+            .iter()
+            .zip(self.widened())
+            .map(move |(v, widened)| if is_fill(&fills, widened) { None } else { Some(v) })
+    }
+
+    /// The `(scale_factor, add_offset)` packing of the variable, or `None`
+    /// if it declares neither. A missing attribute takes its neutral value.
+    fn packing(&self) -> Option<(f64, f64)> {
+        let value = |name| {
+            self.attribute(name)
+                .and_then(|a| a.to_f64_vec())
+                .and_then(|v| v.first().copied())
+        };
+
+        match (value("scale_factor"), value("add_offset")) {
+            (None, None) => None,
+            (scale, offset) => Some((scale.unwrap_or(1.0), offset.unwrap_or(0.0))),
+        }
+    }
+
+    /// Decodes the data to physical values by applying `scale_factor` and
+    /// `add_offset`, with NaN in place of fill and missing values.
+    pub(crate) fn unpacked(&self) -> Vec<f64> {
+        let (scale, offset) = self.packing().unwrap_or((1.0, 0.0));
+
+        self.to_f64_with_nan_fill()
+            .into_iter()
+            .map(|v| v * scale + offset)
+            .collect()
+    }
+
+    /// Lazily decodes the data to physical values, multiplying by
+    /// `scale_factor` and adding `add_offset`. Without either attribute the
+    /// values are just widened. Fill values are transformed like any other
+    /// value.
+    pub fn iter_unpacked(&self) -> impl Iterator<Item = f64> + '_ {
+        let (scale, offset) = self.packing().unwrap_or((1.0, 0.0));
+
+        self.widened().map(move |v| v * scale + offset)
+    }
+
+    /// Decodes the data to physical values: packed variables are unpacked
+    /// with their `scale_factor` and `add_offset`, others are just widened.
+    /// Fill values are left in place.
+    pub fn physical_values(&self) -> Vec<f64> {
+        self.iter_unpacked().collect()
+    }
+
+    /// Renders each physical value with `precision` decimal places for
+    /// display. Fill and missing values are rendered as `NaN`.
+    pub fn formatted(&self, precision: usize) -> Vec<String> {
+        self.unpacked()
+            .into_iter()
+            .map(|v| if v.is_nan() { String::from("NaN") } else { format!("{:.*}", precision, v) })
+            .collect()
+    }
+
+    /// The `(min, max)` bounds of valid data declared by `valid_range`, or by
+    /// `valid_min` and `valid_max`. Missing bounds are unbounded.
+    pub(crate) fn valid_bounds(&self) -> (f64, f64) {
+        let value = |name| self.attribute(name).and_then(|a| a.to_f64_vec());
+
+        match value("valid_range") {
+            Some(range) if range.len() == 2 => (range[0], range[1]),
+            _ => (
+                value("valid_min").and_then(|v| v.first().copied()).unwrap_or(f64::NEG_INFINITY),
+                value("valid_max").and_then(|v| v.first().copied()).unwrap_or(f64::INFINITY),
+            ),
+        }
+    }
+
+    /// Flags each element as valid: not a fill or missing value, and within
+    /// the declared valid range if there is one.
+    pub fn valid_mask(&self) -> Vec<bool> {
+        let fills = self.fill_values();
+        let (min, max) = self.valid_bounds();
+
+        self.widened()
+            .map(|v| !is_fill(&fills, v) && v >= min && v <= max)
+            .collect()
+    }
+
+    /// Counts the elements equal to a fill or missing value.
+    pub fn missing_count(&self) -> usize {
+        let fills = self.fill_values();
+
+        self.widened().filter(|&v| is_fill(&fills, v)).count()
+    }
+
+    /// Whether every element is a fill or missing value, stopping at the
+    /// first one that isn't.
+    pub fn is_all_fill(&self) -> bool {
+        let fills = self.fill_values();
+
+        self.widened().all(|v| is_fill(&fills, v))
+    }
+
+    /// The indices of the first and last elements that aren't fill or
+    /// missing values, or `None` if every element is.
+    pub fn valid_extent(&self) -> Option<(usize, usize)> {
+        let fills = self.fill_values();
+        let mut valid = self
+            .widened()
+            .enumerate()
+            .filter(|&(_, v)| !is_fill(&fills, v))
+            .map(|(i, _)| i);
+
+        let first = valid.next()?;
+        Some((first, valid.last().unwrap_or(first)))
+    }
+
+    /// The runs of consecutive fill or missing values as `(start, length)`
+    /// pairs of flat indices, in order.
+    pub fn fill_runs(&self) -> Vec<(usize, usize)> {
+        let fills = self.fill_values();
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+
+        for (i, v) in self.widened().enumerate() {
+            if !is_fill(&fills, v) {
+                continue;
+            }
+            match runs.last_mut() {
+                Some((start, len)) if *start + *len == i => *len += 1,
+                _ => runs.push((i, 1)),
+            }
+        }
+
+        runs
+    }
+
+    /// The elements that aren't fill or missing values, widened to `f64`,
+    /// paired with their flat index.
+    pub fn sparse(&self) -> Vec<(usize, f64)> {
+        let fills = self.fill_values();
+
+        self.widened()
+            .enumerate()
+            .filter(|&(_, v)| !is_fill(&fills, v))
+            .collect()
+    }
+}
+
+pub(crate) fn is_fill(fills: &[f64], v: f64) -> bool {
+    fills.iter().any(|&f| f == v || (f.is_nan() && v.is_nan()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn it_substitutes_nan_for_fill() {
+        let mut v = float_container("temp", &[0], &[1.0, -999.0, 3.0, 9.96921e36]);
+        v.attributes.push(float_attr("missing_value", &[-999.0]));
+
+        let values = v.to_f64_with_nan_fill();
+        assert_eq!(values[0], 1.0);
+        assert!(values[1].is_nan());
+        assert_eq!(values[2], 3.0);
+        assert!(values[3].is_nan());
+
+        v.attributes.push(float_attr("_FillValue", &[3.0]));
+        let values = v.to_f64_with_nan_fill();
+        assert!(values[2].is_nan());
+        assert!(!values[3].is_nan());
+    }
+
+    #[test]
+    fn it_formats_physical_values() {
+        let mut v = float_container("temp", &[0], &[1.0, -999.0, 2.5, 10.0]);
+        v.attributes.push(float_attr("_FillValue", &[-999.0]));
+        assert_eq!(v.formatted(2), vec!["1.00", "NaN", "2.50", "10.00"]);
+
+        v.attributes.push(float_attr("scale_factor", &[0.5]));
+        v.attributes.push(float_attr("add_offset", &[273.15]));
+        assert_eq!(v.formatted(1), vec!["273.6", "NaN", "274.4", "278.1"]);
+    }
+
+    #[test]
+    fn it_decodes_physical_values() {
+        let mut v = float_container("temp", &[0], &[1.0, 2.5, -999.0]);
+        assert_eq!(v.physical_values(), vec![1.0, 2.5, -999.0]);
+
+        v.attributes.push(float_attr("scale_factor", &[2.0]));
+        assert_eq!(v.physical_values(), vec![2.0, 5.0, -1998.0]);
+
+        v.attributes.push(float_attr("add_offset", &[10.0]));
+        assert_eq!(v.physical_values(), vec![12.0, 15.0, -1988.0]);
+    }
+
+    #[test]
+    fn it_counts_missing_values() {
+        let mut v = float_container("temp", &[0], &[-1.0, 2.0, -1.0, 4.0, -2.0]);
+        assert_eq!(v.missing_count(), 0);
+
+        v.attributes.push(float_attr("_FillValue", &[-1.0]));
+        v.attributes.push(float_attr("missing_value", &[-2.0]));
+        assert_eq!(v.missing_count(), 3);
+    }
+
+    #[test]
+    fn it_detects_all_fill_data() {
+        let mut v = float_container("temp", &[0], &[-1.0, -2.0, -1.0]);
+        assert!(!v.is_all_fill());
+
+        v.attributes.push(float_attr("_FillValue", &[-1.0]));
+        v.attributes.push(float_attr("missing_value", &[-2.0]));
+        assert!(v.is_all_fill());
+
+        let v = float_container("temp", &[0], &[9.96921e36, 1.0]);
+        assert!(!v.is_all_fill());
+    }
+
+    #[test]
+    fn it_finds_the_valid_extent() {
+        let mut v = float_container("temp", &[0], &[-1.0, -1.0, 2.0, -1.0, 4.0, -1.0]);
+        v.attributes.push(float_attr("_FillValue", &[-1.0]));
+        assert_eq!(v.valid_extent(), Some((2, 4)));
+
+        let v = float_container("temp", &[0], &[9.96921e36; 3]);
+        assert_eq!(v.valid_extent(), None);
+    }
+
+    #[test]
+    fn it_lists_sparse_values() {
+        let mut values = vec![0.0; 8];
+        values[1] = 2.5;
+        values[6] = -1.0;
+        let mut v = float_container("precip", &[0], &values);
+        v.attributes.push(float_attr("_FillValue", &[0.0]));
+
+        assert_eq!(v.sparse(), vec![(1, 2.5), (6, -1.0)]);
+    }
+
+    #[test]
+    fn it_masks_valid_data() {
+        let mut v = float_container("temp", &[0], &[-1.0, 2.0, 150.0, 4.0, 9.96921e36]);
+        v.attributes.push(float_attr("missing_value", &[-1.0]));
+        assert_eq!(v.valid_mask(), vec![false, true, true, true, false]);
+
+        v.attributes.push(float_attr("valid_range", &[0.0, 100.0]));
+        assert_eq!(v.valid_mask(), vec![false, true, false, true, false]);
+    }
+
+    #[test]
+    fn it_reads_unsigned_data() {
+        let mut v = NCVariableContainer::<i8> {
+            name: String::from("flags"),
+            dimids: vec![0],
+            attributes: Vec::new(),
+            vsize: 4,
+            offset: 0,
+            data: NCData::new(vec![0xff, 0x01, 0x80, 0xfe]),
+        };
+        v.attributes.push(NCAttribute::Byte(NCAttributeContainer::new("_FillValue", vec![-2])));
+        assert!(!v.is_unsigned());
+        assert_eq!(&v.to_f64_with_nan_fill()[..3], &[-1.0, 1.0, -128.0]);
+
+        v.attributes.push(char_attr("_Unsigned", "true"));
+        assert!(v.is_unsigned());
+        let values = v.to_f64_with_nan_fill();
+        assert_eq!(&values[..3], &[255.0, 1.0, 128.0]);
+        assert!(values[3].is_nan());
+
+        let mut v = NCVariableContainer::<i64> {
+            name: String::from("ids"),
+            dimids: vec![0],
+            attributes: vec![char_attr("_Unsigned", "true")],
+            vsize: 16,
+            offset: 0,
+            data: NCData::new([-1i64, 7].iter().flat_map(|v| v.to_be_bytes()).collect()),
+        };
+        assert_eq!(v.to_f64_with_nan_fill(), [18446744073709551615.0, 7.0]);
+        v.attributes.clear();
+        assert_eq!(v.to_f64_with_nan_fill(), [-1.0, 7.0]);
+    }
+
+    #[test]
+    fn it_finds_fill_runs() {
+        let mut v = float_container("x", &[0], &[-1.0, -1.0, 2.0, -1.0, 3.0, 4.0, -1.0, -1.0, -1.0]);
+        v.attributes.push(float_attr("_FillValue", &[-1.0]));
+        assert_eq!(v.fill_runs(), vec![(0, 2), (3, 1), (6, 3)]);
+
+        let v = float_container("x", &[0], &[1.0, 2.0]);
+        assert!(v.fill_runs().is_empty());
+    }
+
+    #[test]
+    fn it_iterates_unpacked_values() {
+        let mut v = NCVariableContainer::<i16> {
+            name: String::from("packed"),
+            dimids: vec![0],
+            attributes: vec![float_attr("scale_factor", &[0.5]), float_attr("add_offset", &[10.0])],
+            vsize: 4,
+            offset: 0,
+            data: NCData::new(vec![0x00, 0x02, 0xff, 0xfe]),
+        };
+        assert_eq!(v.iter_unpacked().collect::<Vec<_>>(), [11.0, 9.0]);
+
+        v.attributes.remove(0);
+        assert_eq!(v.iter_unpacked().collect::<Vec<_>>(), [12.0, 8.0]);
+        v.attributes.clear();
+        assert_eq!(v.iter_unpacked().collect::<Vec<_>>(), [2.0, -2.0]);
+    }
+
+    #[test]
+    fn it_masks_fill_during_iteration() {
+        let mut v = float_container("temp", &[0], &[1.0, -999.0, 3.0, 9.96921e36, -1.0]);
+        let masked: Vec<_> = v.iter_masked().collect();
+        assert_eq!(masked, [Some(1.0), Some(-999.0), Some(3.0), None, Some(-1.0)]);
+
+        v.attributes.push(float_attr("_FillValue", &[-999.0]));
+        v.attributes.push(float_attr("missing_value", &[-1.0]));
+        let masked: Vec<_> = v.iter_masked().collect();
+        assert_eq!(masked, [Some(1.0), None, Some(3.0), Some(9.96921e36), None]);
+    }
+}
@@ -0,0 +1,136 @@
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::consts::*;
+use crate::reader::*;
+
+/// A summary of a file's header, gathered without decoding any attribute
+/// values or variable data.
+#[derive(Debug)]
+pub struct IndexEntry {
+    pub version: u8,
+    pub dimensions: Vec<NCDimension>,
+    pub variables: Vec<IndexVariable>,
+}
+
+#[derive(Debug)]
+pub struct IndexVariable {
+    pub name: String,
+    pub nc_type: NCType,
+}
+
+impl NCFile {
+    /// Reads just enough of the header at `path` to catalogue the file.
+    /// Attribute values are skipped over and variable data is never read,
+    /// which makes this much cheaper than a full `open`.
+    pub fn index_entry<P: AsRef<Path>>(path: P) -> Result<IndexEntry> {
+        let mut r = io::BufReader::new(fs::File::open(path)?);
+
+        Self::validate_magic_number(&mut r)?;
+        let version = read_u8(&mut r)?;
+        // counts and lengths are 8 bytes wide in CDF-5
+        let width = if version == VERSION_CDF5 { 8 } else { 4 };
+        // numrecs
+        read_non_neg(&mut r, version)?;
+
+        let mut dimensions = Vec::new();
+        let dimflag = read_u32(&mut r)? as u8;
+        let len = read_non_neg(&mut r, version)?;
+        if dimflag == NC_DIMENSION {
+            for _ in 0..len {
+                let name = read_string(&mut r, version)?;
+                let length = read_non_neg(&mut r, version)?;
+                dimensions.push(NCDimension { name, length });
+            }
+        }
+
+        skip_attrlist(&mut r, version)?;
+
+        let mut variables = Vec::new();
+        let varflag = read_u32(&mut r)? as u8;
+        let len = read_non_neg(&mut r, version)?;
+        if varflag == NC_VARIABLE {
+            for _ in 0..len {
+                let name = read_string(&mut r, version)?;
+                let ndims = read_non_neg(&mut r, version)?;
+                let dimids = i64::try_from(ndims)
+                    .ok()
+                    .and_then(|n| n.checked_mul(width))
+                    .ok_or_else(|| ParseError::new(&format!("{} dimensions are too many", ndims)))?;
+                r.seek_relative(dimids)?;
+                skip_attrlist(&mut r, version)?;
+
+                let code = read_u32(&mut r)? as u8;
+                let nc_type = NCType::from_code(code).ok_or(ParseError::UnknownType(code))?;
+                // vsize and begin
+                r.seek_relative(width + if version == 0x1 { 4 } else { 8 })?;
+
+                variables.push(IndexVariable { name, nc_type });
+            }
+        }
+
+        Ok(IndexEntry {
+            version,
+            dimensions,
+            variables,
+        })
+    }
+}
+
+fn skip_attrlist(r: &mut io::BufReader<fs::File>, version: u8) -> Result<()> {
+    // attribute flag, which is followed by the count either way
+    read_u32(r)?;
+    let len = read_non_neg(r, version)?;
+
+    for _ in 0..len {
+        read_string(r, version)?;
+        let nctype = read_u32(r)? as u8;
+        let nelems = read_non_neg(r, version)?;
+        let size = nc_type_size(nctype).ok_or(ParseError::UnknownType(nctype))?;
+        let too_many = || ParseError::new(&format!("{} values are too many", nelems));
+        let bytes = nelems.checked_mul(size as u64).ok_or_else(too_many)?;
+        let len = checked_padded_len(to_usize(bytes)?)?;
+        r.seek_relative(i64::try_from(len).map_err(|_| too_many())?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    #[test]
+    fn it_builds_index_entries() {
+        let entry = NCFile::index_entry(SAMPLE_FILE_1).unwrap();
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+
+        assert_eq!(entry.version, f.version);
+        assert_eq!(entry.dimensions.len(), f.dimensions.len());
+
+        let names: Vec<&str> = entry.variables.iter().map(|v| v.name.as_str()).collect();
+        let expected: Vec<String> = f.variables.iter().map(|v| match v {
+            NCVariable::Float(c) => c.name.clone(),
+            NCVariable::Int(c) => c.name.clone(),
+            NCVariable::Short(c) => c.name.clone(),
+            _ => String::new(),
+        }).collect();
+        assert_eq!(names, expected);
+        assert_eq!(entry.variables[0].nc_type, NCType::Float);
+        assert_eq!(entry.variables[3].nc_type, NCType::Short);
+    }
+
+    #[test]
+    fn it_indexes_cdf5() {
+        let entry = NCFile::index_entry("./samples/sample3.nc").unwrap();
+
+        assert_eq!(entry.version, 5);
+        assert_eq!(entry.dimensions[0].length, 5_000_000_000);
+        assert_eq!(entry.variables.len(), 1);
+        assert_eq!(entry.variables[0].nc_type, NCType::Double);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use serde_json::{Map, Value};
+
+use crate::reader::*;
+
+impl NCAttribute {
+    /// Builds an attribute from a JSON value. Strings become `Char`
+    /// attributes, integers or arrays of integers become `Int` attributes,
+    /// and any other numbers or arrays of numbers become `Double` attributes.
+    /// Anything else, including arrays mixing strings and numbers, is
+    /// rejected.
+    pub fn from_json(name: &str, value: &Value) -> Result<NCAttribute> {
+        let unsupported =
+            || ParseError::new(&format!("unsupported JSON value for attribute {}", name));
+
+        let numbers = match value {
+            Value::String(s) => {
+                return Ok(NCAttribute::Char(NCAttributeContainer::new(name, s.chars().collect())));
+            }
+            Value::Number(_) => vec![value],
+            Value::Array(values) if !values.is_empty() => values.iter().collect(),
+            _ => return Err(unsupported()),
+        };
+
+        if !numbers.iter().all(|v| v.is_number()) {
+            return Err(unsupported());
+        }
+
+        let ints: Option<Vec<i32>> = numbers
+            .iter()
+            .map(|v| v.as_i64().and_then(|i| i32::try_from(i).ok()))
+            .collect();
+
+        Ok(match ints {
+            Some(ints) => NCAttribute::Int(NCAttributeContainer::new(name, ints)),
+            None => NCAttribute::Double(NCAttributeContainer::new(
+                name,
+                numbers.iter().filter_map(|v| v.as_f64()).collect(),
+            )),
+        })
+    }
+}
+
+impl NCAttribute {
+    /// The attribute's values as JSON: a string for `Char` attributes, a
+    /// number for single values and an array of numbers otherwise. NaNs and
+    /// infinities, which JSON can't represent, become `null`.
+    pub fn to_json(&self) -> Value {
+        fn numbers<T: Copy>(values: &[T]) -> Value
+        where
+            Value: From<T>,
+        {
+            match values {
+                [v] => Value::from(*v),
+                _ => values.iter().map(|&v| Value::from(v)).collect(),
+            }
+        }
+
+        match self {
+            NCAttribute::Char(c) => Value::String(c.to_string()),
+            NCAttribute::Byte(c) => numbers(&c.values),
+            NCAttribute::Short(c) => numbers(&c.values),
+            NCAttribute::Int(c) => numbers(&c.values),
+            NCAttribute::Float(c) => numbers(&c.values),
+            NCAttribute::Double(c) => numbers(&c.values),
+            NCAttribute::UByte(c) => numbers(&c.values),
+            NCAttribute::UShort(c) => numbers(&c.values),
+            NCAttribute::UInt(c) => numbers(&c.values),
+            NCAttribute::Int64(c) => numbers(&c.values),
+            NCAttribute::UInt64(c) => numbers(&c.values),
+        }
+    }
+}
+
+impl NCFile {
+    /// The file's metadata as a context for templating engines, with three
+    /// entries: `dimensions` maps dimension names to lengths, `global` maps
+    /// global attribute names to their values, and `variables` maps
+    /// variable names to objects holding the variable's `type`,
+    /// `dimensions` and `attributes`.
+    pub fn as_context(&self) -> BTreeMap<String, Value> {
+        let attributes = |attrs: &[NCAttribute]| -> Value {
+            let map: Map<String, Value> =
+                attrs.iter().map(|a| (a.name().to_string(), a.to_json())).collect();
+            Value::Object(map)
+        };
+
+        let dimensions: Map<String, Value> = self
+            .dimensions
+            .iter()
+            .map(|d| (d.name.clone(), Value::from(d.length)))
+            .collect();
+
+        let variables: Map<String, Value> = self
+            .variables
+            .iter()
+            .map(|v| {
+                let mut entry = Map::new();
+                entry.insert("type".to_string(), Value::from(v.nc_type().name()));
+                entry.insert("dimensions".to_string(), Value::from(self.dimension_names(v)));
+                entry.insert("attributes".to_string(), attributes(v.attributes()));
+                (v.name().to_string(), Value::Object(entry))
+            })
+            .collect();
+
+        let mut context = BTreeMap::new();
+        context.insert("dimensions".to_string(), Value::Object(dimensions));
+        context.insert("global".to_string(), attributes(&self.attributes));
+        context.insert("variables".to_string(), Value::Object(variables));
+        context
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::reader::*;
+
+    #[test]
+    fn it_builds_attributes_from_json() {
+        let a = NCAttribute::from_json("units", &json!("degrees_east")).unwrap();
+        assert_eq!(a.as_text().as_deref(), Some("degrees_east"));
+
+        match NCAttribute::from_json("valid_range", &json!([0, 100])).unwrap() {
+            NCAttribute::Int(c) => assert_eq!(c.values, vec![0, 100]),
+            a => panic!("expected Int, got {:?}", a),
+        }
+
+        match NCAttribute::from_json("scale_factor", &json!(0.5)).unwrap() {
+            NCAttribute::Double(c) => assert_eq!(c.values, vec![0.5]),
+            a => panic!("expected Double, got {:?}", a),
+        }
+
+        assert!(NCAttribute::from_json("mixed", &json!([1, "two"])).is_err());
+        assert!(NCAttribute::from_json("flag", &json!(true)).is_err());
+        assert!(NCAttribute::from_json("empty", &json!([])).is_err());
+    }
+
+    #[test]
+    fn it_builds_template_contexts() {
+        let f = NCFile::open("./samples/sample1.nc").unwrap();
+        let context = f.as_context();
+
+        assert_eq!(context["dimensions"]["longitude"], json!(700));
+        assert_eq!(context["global"]["Conventions"], json!("CF-1.6"));
+
+        let lon = &context["variables"]["longitude"];
+        assert_eq!(lon["type"], json!("float"));
+        assert_eq!(lon["dimensions"], json!(["longitude"]));
+        assert_eq!(lon["attributes"]["units"], json!("degrees_east"));
+        assert_eq!(context["variables"]["unknown"]["attributes"]["_FillValue"], json!(-32767));
+    }
+}
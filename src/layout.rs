@@ -0,0 +1,339 @@
+use std::io::{self, Read};
+use std::time::Instant;
+
+use crate::consts::*;
+use crate::reader::*;
+
+/// Where a variable's data is stored: `count` runs of `len` bytes, each
+/// `stride` bytes after the previous one, starting at `offset`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DataExtent {
+    pub offset: u64,
+    pub len: usize,
+    pub count: usize,
+    pub stride: u64,
+}
+
+impl DataExtent {
+    /// The number of data bytes, excluding the gaps between runs.
+    pub fn total(&self) -> usize {
+        self.len.saturating_mul(self.count)
+    }
+
+    /// Reads up to `limit` bytes of the data, concatenating the runs. If
+    /// `allow_truncated` is set, a short read ends the data early instead
+    /// of failing. Fails if `deadline` passes before a run is read.
+    pub fn read<R>(
+        &self,
+        r: &mut R,
+        limit: usize,
+        allow_truncated: bool,
+        deadline: Option<Instant>,
+    ) -> Result<Vec<u8>>
+    where
+        R: io::Read + io::Seek + ?Sized,
+    {
+        // the extent comes from the header, so the buffer only grows as
+        // bytes arrive rather than being allocated up front
+        let mut buf = Vec::new();
+
+        for i in 0..self.count {
+            let want = self.len.min(limit - buf.len());
+            if want == 0 {
+                break;
+            }
+            check_deadline(deadline)?;
+
+            let start = (i as u64)
+                .checked_mul(self.stride)
+                .and_then(|gap| gap.checked_add(self.offset))
+                .ok_or_else(|| ParseError::new("record offset is too large"))?;
+            r.seek(io::SeekFrom::Start(start))?;
+            let read = (&mut *r).take(want as u64).read_to_end(&mut buf)?;
+            if read < want {
+                if allow_truncated {
+                    break;
+                }
+                return Err(ParseError::UnexpectedEof);
+            }
+        }
+
+        Ok(buf)
+    }
+
+    /// Reads `len` bytes beginning `start` bytes into the data, as though
+    /// the runs were concatenated, without reading the rest.
+    pub fn read_range<R>(&self, r: &mut R, start: usize, len: usize) -> Result<Vec<u8>>
+    where
+        R: io::Read + io::Seek + ?Sized,
+    {
+        let mut buf = Vec::new();
+        let mut pos = start;
+
+        while buf.len() < len {
+            let run = pos.checked_div(self.len).unwrap_or(usize::MAX);
+            if run >= self.count {
+                return Err(ParseError::UnexpectedEof);
+            }
+            let within = pos % self.len;
+            let want = (self.len - within).min(len - buf.len());
+
+            let at = (run as u64)
+                .checked_mul(self.stride)
+                .and_then(|gap| gap.checked_add(self.offset))
+                .and_then(|at| at.checked_add(within as u64))
+                .ok_or_else(|| ParseError::new("record offset is too large"))?;
+            r.seek(io::SeekFrom::Start(at))?;
+            if (&mut *r).take(want as u64).read_to_end(&mut buf)? < want {
+                return Err(ParseError::UnexpectedEof);
+            }
+            pos += want;
+        }
+
+        Ok(buf)
+    }
+}
+
+impl NCFile {
+    /// The size in bytes of one record, i.e. the sum of the per-record sizes
+    /// of all record variables. A lone record variable isn't padded.
+    pub(crate) fn record_size(&self) -> u64 {
+        match self.record_variables()[..] {
+            [v] => self.record_len(v) as u64,
+            ref vars => vars.iter().fold(0, |size, v| size.saturating_add(v.layout().0)),
+        }
+    }
+
+    /// The size in bytes of one record of a record variable, without
+    /// padding.
+    pub(crate) fn record_len(&self, var: &NCVariable) -> usize {
+        let dimids = var.dimids().get(1..).unwrap_or_default();
+        self.resolve_shape(dimids)
+            .iter()
+            .fold(var.nc_type().size(), |len, &n| len.saturating_mul(n))
+    }
+
+    /// Where the data of `var` is stored. Fixed-size variables occupy one
+    /// run of up to `vsize` bytes, leaving out the padding after the last
+    /// element; record variables occupy one run per record.
+    pub(crate) fn data_extent(&self, var: &NCVariable) -> DataExtent {
+        let (vsize, offset) = var.layout();
+
+        if self.is_record_variable(var) {
+            DataExtent {
+                offset,
+                len: self.record_len(var),
+                count: self.numrecs as usize,
+                stride: self.record_size(),
+            }
+        } else {
+            let len = self
+                .resolve_shape(var.dimids())
+                .iter()
+                .fold(var.nc_type().size(), |len, &n| len.saturating_mul(n));
+            DataExtent { offset, len: len.min(vsize as usize), count: 1, stride: 0 }
+        }
+    }
+
+    /// The `vsize` a variable of `element_count` elements of type `nc_type`
+    /// occupies when written, including padding to a 4-byte boundary.
+    /// Returns `None` if that doesn't fit in a `usize`.
+    pub fn planned_vsize(&self, element_count: usize, nc_type: NCType) -> Option<usize> {
+        checked_padded_len(element_count.checked_mul(nc_type.size())?).ok()
+    }
+
+    /// The size in bytes of the header as this file would be written, from
+    /// the magic number to the end of the variable list.
+    pub fn header_len(&self) -> u64 {
+        // counts and lengths are 8 bytes wide in CDF-5
+        let width = if self.version == VERSION_CDF5 { 8 } else { 4 };
+        let name = |n: &str| width + padded_len(n.len());
+        let attributes = |attrs: &[NCAttribute]| -> usize {
+            4 + width + attrs
+                .iter()
+                .map(|a| {
+                    let count = each_attribute!(a, c => c.values.len());
+                    name(a.name()) + 4 + width + padded_len(count * a.nc_type().size())
+                })
+                .sum::<usize>()
+        };
+        let offset_size = if self.version == 1 { 4 } else { 8 };
+
+        let dimensions: usize = self.dimensions.iter().map(|d| name(&d.name) + width).sum();
+        let variables: usize = self
+            .variables
+            .iter()
+            .map(|v| {
+                name(v.name()) + width * (1 + v.dimids().len()) + attributes(v.attributes()) + 4 + width + offset_size
+            })
+            .sum();
+
+        (4 + width + 4 + width + dimensions + attributes(&self.attributes) + 4 + width + variables) as u64
+    }
+
+    /// The expected size of the whole file: the header plus the data of
+    /// every fixed-size variable and every record, padding included. A file
+    /// on disk that's shorter than this has been truncated. Returns `None`
+    /// if the size doesn't fit in a `u64`.
+    pub fn total_size(&self) -> Option<u64> {
+        let fixed = self
+            .variables
+            .iter()
+            .filter(|v| !self.is_record_variable(v))
+            .try_fold(0u64, |sum, v| sum.checked_add(v.layout().0))?;
+
+        self.header_len()
+            .checked_add(fixed)?
+            .checked_add(self.numrecs.checked_mul(self.record_size())?)
+    }
+
+    /// The `(start, end)` byte range holding the data of the variable `name`,
+    /// suitable for an HTTP range request. For record variables this spans
+    /// every record, including the interleaved data of other record
+    /// variables. Returns `None` if there's no such variable or the range
+    /// ends past what a `u64` can hold.
+    pub fn variable_byte_range(&self, name: &str) -> Option<(u64, u64)> {
+        let var = self.variable(name)?;
+        let (vsize, offset) = var.layout();
+
+        if self.is_record_variable(var) {
+            if self.numrecs == 0 {
+                return Some((offset, offset));
+            }
+            let records = self.numrecs - 1;
+            let end = records
+                .checked_mul(self.record_size())?
+                .checked_add(offset)?
+                .checked_add(vsize)?;
+            Some((offset, end))
+        } else {
+            Some((offset, offset.checked_add(vsize)?))
+        }
+    }
+
+    /// The `(start, end)` byte range of the largest run of variable data
+    /// with no gaps between neighbouring variables, so it can be fetched in
+    /// a single read. The interleaved record section counts as one region.
+    /// Returns `None` if there's no variable data or a region ends past what
+    /// a `u64` can hold.
+    pub fn largest_contiguous_block(&self) -> Option<(u64, u64)> {
+        let mut regions: Vec<(u64, u64)> = self
+            .variables
+            .iter()
+            .filter(|v| !self.is_record_variable(v))
+            .map(|v| {
+                let (vsize, offset) = v.layout();
+                Some((offset, offset.checked_add(vsize)?))
+            })
+            .collect::<Option<_>>()?;
+
+        if let Some(start) = self.record_variables().iter().map(|v| v.layout().1).min() {
+            let len = self.numrecs.checked_mul(self.record_size())?;
+            regions.push((start, start.checked_add(len)?));
+        }
+
+        regions.sort_unstable();
+
+        let mut runs: Vec<(u64, u64)> = Vec::new();
+        for (start, end) in regions {
+            match runs.last_mut() {
+                Some(run) if start <= run.1 => run.1 = run.1.max(end),
+                _ => runs.push((start, end)),
+            }
+        }
+
+        runs.into_iter().rev().max_by_key(|(start, end)| end - start)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    /// A file of 2^40 records of a variable 2^40 floats long, whose record
+    /// section is too large for a `u64`.
+    fn huge_records() -> NCFile {
+        let mut f = file(
+            vec![dim("time", 0), dim("x", 1 << 40)],
+            vec![float_var("v", &[0, 1], &[])],
+        );
+        f.version = 5;
+        f.numrecs = 1 << 40;
+        f
+    }
+
+    #[test]
+    fn it_returns_variable_byte_ranges() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+
+        let (start, end) = f.variable_byte_range("longitude").unwrap();
+        assert_eq!(end - start, 700 * 4);
+        assert_eq!(f.variable_byte_range("latitude").unwrap().0, end);
+        assert_eq!(f.variable_byte_range("missing"), None);
+    }
+
+    #[test]
+    fn it_plans_padded_vsizes() {
+        let f = file(Vec::new(), Vec::new());
+
+        assert_eq!(f.planned_vsize(3, NCType::Float), Some(12));
+        assert_eq!(f.planned_vsize(5, NCType::Byte), Some(8));
+        assert_eq!(f.planned_vsize(0, NCType::Double), Some(0));
+        assert_eq!(f.planned_vsize(usize::MAX / 2, NCType::Short), None);
+        assert_eq!(f.planned_vsize(usize::MAX, NCType::Byte), None);
+    }
+
+    #[test]
+    fn it_computes_the_total_size() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let first_offset = f.variables.iter().map(|v| v.layout().1).min().unwrap();
+
+        assert_eq!(f.header_len(), first_offset);
+        assert_eq!(f.total_size(), Some(std::fs::metadata(SAMPLE_FILE_1).unwrap().len()));
+
+        assert_eq!(huge_records().total_size(), None);
+    }
+
+    #[test]
+    fn it_spans_all_records() {
+        let mut a = float_container("a", &[0, 1], &[0.0; 2]);
+        a.offset = 100;
+        let mut b = float_container("b", &[0], &[0.0]);
+        b.offset = 108;
+
+        let mut f = file(
+            vec![dim("time", 0), dim("x", 2)],
+            vec![NCVariable::Float(a), NCVariable::Float(b)],
+        );
+        f.numrecs = 3;
+
+        assert!(f.is_record_variable(&f.variables[0]));
+        assert_eq!(f.variable_byte_range("a"), Some((100, 132)));
+        assert_eq!(f.variable_byte_range("b"), Some((108, 136)));
+
+        assert_eq!(huge_records().variable_byte_range("v"), None);
+    }
+
+    #[test]
+    fn it_finds_the_largest_contiguous_block() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let start = f.variable_byte_range("longitude").unwrap().0;
+        let end = f.variable_byte_range("unknown").unwrap().1;
+        assert_eq!(f.largest_contiguous_block(), Some((start, end)));
+
+        let mut vars = Vec::new();
+        for (name, offset, len) in [("a", 100, 2), ("b", 108, 1), ("c", 200, 1), ("d", 204, 1)] {
+            let mut c = float_container(name, &[0], &vec![0.0; len]);
+            c.offset = offset;
+            vars.push(NCVariable::Float(c));
+        }
+        let f = file(vec![dim("x", 2)], vars);
+        assert_eq!(f.largest_contiguous_block(), Some((100, 112)));
+
+        assert_eq!(file(Vec::new(), Vec::new()).largest_contiguous_block(), None);
+        assert_eq!(huge_records().largest_contiguous_block(), None);
+    }
+}
@@ -1,7 +1,43 @@
 mod consts;
+#[macro_use]
 mod reader;
+mod ops;
+mod cdl;
+mod index;
+mod fill;
+mod cf;
+mod validate;
+mod endian;
+mod writer;
+mod layout;
+mod stats;
+mod schema;
+mod summary;
+mod coords;
+mod cache;
+mod dot;
+mod slice;
+#[cfg(feature = "serde")]
+mod json;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "ndarray")]
+mod array;
+#[cfg(test)]
+mod test_util;
 
+pub use crate::consts::{
+    DEFAULT_FILL_BYTE, DEFAULT_FILL_CHAR, DEFAULT_FILL_DOUBLE, DEFAULT_FILL_FLOAT,
+    DEFAULT_FILL_INT, DEFAULT_FILL_INT64, DEFAULT_FILL_SHORT, DEFAULT_FILL_UBYTE,
+    DEFAULT_FILL_UINT, DEFAULT_FILL_UINT64, DEFAULT_FILL_USHORT,
+};
 pub use crate::reader::*;
+pub use crate::index::*;
+pub use crate::cf::*;
+pub use crate::endian::*;
+pub use crate::summary::*;
+pub use crate::cache::*;
+pub use crate::coords::*;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,79 @@
+use crate::reader::*;
+
+impl NCFile {
+    /// Checks whether two variables can be combined element-wise under
+    /// NumPy-style broadcasting: aligning their shapes from the trailing
+    /// dimension, each pair of lengths must be equal or one of them must be 1.
+    pub fn broadcast_compatible(&self, a: &NCVariable, b: &NCVariable) -> bool {
+        let a = self.resolve_shape(a.dimids());
+        let b = self.resolve_shape(b.dimids());
+
+        a.iter()
+            .rev()
+            .zip(b.iter().rev())
+            .all(|(&x, &y)| x == y || x == 1 || y == 1)
+    }
+
+    /// Adds two numeric variables of identical shape element-wise, widening
+    /// both to `f64`.
+    pub fn add_variables(&self, a: &str, b: &str) -> Result<Vec<f64>> {
+        let (x, y) = (self.numeric_variable(a)?, self.numeric_variable(b)?);
+
+        if self.resolve_shape(x.dimids()) != self.resolve_shape(y.dimids()) {
+            return Err(ParseError::new(&format!(
+                "variables {} and {} have different shapes", a, b
+            )));
+        }
+
+        let x = x.to_f64_vec().unwrap_or_default();
+        let y = y.to_f64_vec().unwrap_or_default();
+
+        Ok(x.iter().zip(y.iter()).map(|(x, y)| x + y).collect())
+    }
+
+    fn numeric_variable(&self, name: &str) -> Result<&NCVariable> {
+        match self.variable(name) {
+            Some(NCVariable::Char(_)) => Err(ParseError::new(&format!(
+                "variable {} is not numeric", name
+            ))),
+            Some(v) => Ok(v),
+            None => Err(ParseError::new(&format!("no variable named {}", name))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::*;
+
+    #[test]
+    fn it_checks_broadcast_compatibility() {
+        let f = file(
+            vec![dim("y", 2), dim("x", 3), dim("z", 2)],
+            vec![
+                float_var("grid", &[0, 1], &[0.0; 6]),
+                float_var("row", &[1], &[0.0; 3]),
+                float_var("other", &[2], &[0.0; 2]),
+            ],
+        );
+
+        assert!(f.broadcast_compatible(&f.variables[0], &f.variables[1]));
+        assert!(f.broadcast_compatible(&f.variables[1], &f.variables[0]));
+        assert!(!f.broadcast_compatible(&f.variables[0], &f.variables[2]));
+    }
+
+    #[test]
+    fn it_adds_variables() {
+        let f = file(
+            vec![dim("x", 3), dim("y", 2)],
+            vec![
+                float_var("a", &[0], &[1.5, -2.0, 4.25]),
+                float_var("b", &[1], &[1.0, 2.0]),
+            ],
+        );
+
+        assert_eq!(f.add_variables("a", "a").unwrap(), vec![3.0, -4.0, 8.5]);
+        assert!(f.add_variables("a", "b").is_err());
+        assert!(f.add_variables("a", "missing").is_err());
+    }
+}
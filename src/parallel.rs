@@ -0,0 +1,149 @@
+use rayon::prelude::*;
+
+use crate::reader::*;
+
+impl<T: Send + Sync> NCVariableContainer<T>
+where
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+{
+    /// Splits the variable, laid out row-major with the given `shape`, into
+    /// tiles of extent `tile` and decodes them in parallel. Each tile is
+    /// returned with the index of its origin; tiles on the far edges are
+    /// clipped to the shape.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shape` and `tile` have different lengths or a tile extent
+    /// is zero.
+    pub fn par_tiles(&self, shape: &[usize], tile: &[usize]) -> Vec<(Vec<usize>, Vec<T>)> {
+        assert_eq!(shape.len(), tile.len(), "tile rank must match the shape");
+        assert!(tile.iter().all(|&t| t > 0), "tile extents must be non-zero");
+
+        tile_origins(shape, tile)
+            .into_par_iter()
+            .map(|origin| {
+                let data = self.decode_tile(shape, tile, &origin);
+                (origin, data)
+            })
+            .collect()
+    }
+
+    fn decode_tile(&self, shape: &[usize], tile: &[usize], origin: &[usize]) -> Vec<T> {
+        let size = NCDataIter::<T>::element_size();
+        let raw = self.data.raw();
+        let extent: Vec<usize> = (0..shape.len())
+            .map(|i| tile[i].min(shape[i] - origin[i]))
+            .collect();
+
+        let mut values = Vec::with_capacity(extent.iter().product());
+        let rank = shape.len();
+        if rank == 0 {
+            values.extend(NCDataIter::new(raw).take(1));
+            return values;
+        }
+
+        // walk each contiguous run along the last axis
+        let mut index = origin.to_vec();
+        loop {
+            let flat = index.iter().zip(shape).fold(0, |acc, (&i, &n)| acc * n + i);
+            let start = (flat * size).min(raw.len());
+            let end = ((flat + extent[rank - 1]) * size).min(raw.len());
+            values.extend(NCDataIter::new(&raw[start..end]));
+
+            // advance the outer axes
+            let mut axis = rank - 1;
+            loop {
+                if axis == 0 {
+                    return values;
+                }
+                axis -= 1;
+                index[axis] += 1;
+                if index[axis] < origin[axis] + extent[axis] {
+                    break;
+                }
+                index[axis] = origin[axis];
+            }
+        }
+    }
+}
+
+fn tile_origins(shape: &[usize], tile: &[usize]) -> Vec<Vec<usize>> {
+    let mut origins = vec![Vec::new()];
+
+    for (&n, &t) in shape.iter().zip(tile) {
+        origins = origins
+            .into_iter()
+            .flat_map(|o| {
+                (0..n).step_by(t).map(move |i| {
+                    let mut o = o.clone();
+                    o.push(i);
+                    o
+                })
+            })
+            .collect();
+    }
+
+    origins
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn it_decodes_tiles_in_parallel() {
+        let values: Vec<f32> = (0..16).map(|v| v as f32).collect();
+        let v = float_container("grid", &[0, 1], &values);
+
+        let tiles = v.par_tiles(&[4, 4], &[2, 2]);
+        assert_eq!(tiles.len(), 4);
+        assert_eq!(tiles[1], (vec![0, 2], vec![2.0, 3.0, 6.0, 7.0]));
+
+        let mut grid = vec![0.0; 16];
+        for (origin, data) in tiles {
+            for (i, value) in data.into_iter().enumerate() {
+                grid[(origin[0] + i / 2) * 4 + origin[1] + i % 2] = value;
+            }
+        }
+        assert_eq!(grid, values);
+    }
+
+    #[test]
+    fn it_tiles_single_byte_elements() {
+        let c = NCVariableContainer::<char> {
+            name: String::from("text"),
+            dimids: vec![0, 1],
+            attributes: Vec::new(),
+            vsize: 8,
+            offset: 0,
+            data: NCData::new(b"abcdefgh".to_vec()),
+        };
+
+        let tiles = c.par_tiles(&[2, 4], &[1, 2]);
+        assert_eq!(tiles.len(), 4);
+        assert_eq!(tiles[0], (vec![0, 0], vec!['a', 'b']));
+        assert_eq!(tiles[1], (vec![0, 2], vec!['c', 'd']));
+        assert_eq!(tiles[3], (vec![1, 2], vec!['g', 'h']));
+
+        let c = NCVariableContainer::<i8> {
+            name: String::from("bytes"),
+            dimids: vec![0, 1],
+            attributes: Vec::new(),
+            vsize: 4,
+            offset: 0,
+            data: NCData::new(vec![1, 2, 3, 0xff]),
+        };
+        assert_eq!(c.par_tiles(&[2, 2], &[2, 1])[1], (vec![0, 1], vec![2, -1]));
+    }
+
+    #[test]
+    fn it_clips_edge_tiles() {
+        let values: Vec<f32> = (0..9).map(|v| v as f32).collect();
+        let v = float_container("grid", &[0, 1], &values);
+
+        let tiles = v.par_tiles(&[3, 3], &[2, 2]);
+        assert_eq!(tiles.len(), 4);
+        assert_eq!(tiles[3], (vec![2, 2], vec![8.0]));
+    }
+}
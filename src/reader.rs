@@ -1,31 +1,227 @@
 use std::io;
 use std::fs;
+use std::any::Any;
+use std::borrow::Cow;
 use std::fmt;
+use std::iter;
 use std::result;
 use std::error::Error;
 use std::path::Path;
 use std::convert::From;
+use std::convert::TryFrom;
+use std::convert::TryInto;
 use std::string::FromUtf8Error;
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
 
 use crate::consts::*;
+use crate::layout::DataExtent;
+
+/// The external data types of the classic format, plus the unsigned and
+/// 64-bit integer types added by CDF-5.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NCType {
+    Byte,
+    Char,
+    Short,
+    Int,
+    Float,
+    Double,
+    UByte,
+    UShort,
+    UInt,
+    Int64,
+    UInt64,
+}
 
-#[derive(Debug)]
+impl NCType {
+    /// The type for an `NC_*` code as stored in the header.
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            NC_BYTE => Some(NCType::Byte),
+            NC_CHAR => Some(NCType::Char),
+            NC_SHORT => Some(NCType::Short),
+            NC_INT => Some(NCType::Int),
+            NC_FLOAT => Some(NCType::Float),
+            NC_DOUBLE => Some(NCType::Double),
+            NC_UBYTE => Some(NCType::UByte),
+            NC_USHORT => Some(NCType::UShort),
+            NC_UINT => Some(NCType::UInt),
+            NC_INT64 => Some(NCType::Int64),
+            NC_UINT64 => Some(NCType::UInt64),
+            _ => None,
+        }
+    }
+
+    /// The `NC_*` code stored in the header for this type.
+    pub fn code(self) -> u8 {
+        match self {
+            NCType::Byte => NC_BYTE,
+            NCType::Char => NC_CHAR,
+            NCType::Short => NC_SHORT,
+            NCType::Int => NC_INT,
+            NCType::Float => NC_FLOAT,
+            NCType::Double => NC_DOUBLE,
+            NCType::UByte => NC_UBYTE,
+            NCType::UShort => NC_USHORT,
+            NCType::UInt => NC_UINT,
+            NCType::Int64 => NC_INT64,
+            NCType::UInt64 => NC_UINT64,
+        }
+    }
+
+    /// Size in bytes of a single element.
+    pub fn size(self) -> usize {
+        match self {
+            NCType::Byte | NCType::Char | NCType::UByte => 1,
+            NCType::Short | NCType::UShort => 2,
+            NCType::Int | NCType::Float | NCType::UInt => 4,
+            NCType::Double | NCType::Int64 | NCType::UInt64 => 8,
+        }
+    }
+
+    /// The CDL name of the type, such as `float`.
+    pub fn name(self) -> &'static str {
+        match self {
+            NCType::Byte => "byte",
+            NCType::Char => "char",
+            NCType::Short => "short",
+            NCType::Int => "int",
+            NCType::Float => "float",
+            NCType::Double => "double",
+            NCType::UByte => "ubyte",
+            NCType::UShort => "ushort",
+            NCType::UInt => "uint",
+            NCType::Int64 => "int64",
+            NCType::UInt64 => "uint64",
+        }
+    }
+
+    /// The fill value the netCDF libraries use for this type, as a single
+    /// valued `_FillValue` attribute of the type.
+    pub fn default_fill(self) -> NCAttribute {
+        fn fill<T>(value: T) -> NCAttributeContainer<T> {
+            NCAttributeContainer::new("_FillValue", vec![value])
+        }
+
+        match self {
+            NCType::Byte => NCAttribute::Byte(fill(DEFAULT_FILL_BYTE)),
+            NCType::Char => NCAttribute::Char(fill(DEFAULT_FILL_CHAR)),
+            NCType::Short => NCAttribute::Short(fill(DEFAULT_FILL_SHORT)),
+            NCType::Int => NCAttribute::Int(fill(DEFAULT_FILL_INT)),
+            NCType::Float => NCAttribute::Float(fill(DEFAULT_FILL_FLOAT)),
+            NCType::Double => NCAttribute::Double(fill(DEFAULT_FILL_DOUBLE)),
+            NCType::UByte => NCAttribute::UByte(fill(DEFAULT_FILL_UBYTE)),
+            NCType::UShort => NCAttribute::UShort(fill(DEFAULT_FILL_USHORT)),
+            NCType::UInt => NCAttribute::UInt(fill(DEFAULT_FILL_UINT)),
+            NCType::Int64 => NCAttribute::Int64(fill(DEFAULT_FILL_INT64)),
+            NCType::UInt64 => NCAttribute::UInt64(fill(DEFAULT_FILL_UINT64)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct NCDimension {
     pub name: String,
-    pub length: u32,
+    pub length: u64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum NCAttribute {
-    Byte(NCAttributeContainer<u8>),
+    Byte(NCAttributeContainer<i8>),
     Char(NCAttributeContainer<char>),
     Short(NCAttributeContainer<i16>),
     Int(NCAttributeContainer<i32>),
     Float(NCAttributeContainer<f32>),
     Double(NCAttributeContainer<f64>),
+    UByte(NCAttributeContainer<u8>),
+    UShort(NCAttributeContainer<u16>),
+    UInt(NCAttributeContainer<u32>),
+    Int64(NCAttributeContainer<i64>),
+    UInt64(NCAttributeContainer<u64>),
+}
+
+/// Expands `$body` once for every arm of an `NCAttribute`, binding the inner
+/// container to `$c`.
+macro_rules! each_attribute {
+    ($attr:expr, $c:ident => $body:expr) => {
+        match $attr {
+            NCAttribute::Byte($c) => $body,
+            NCAttribute::Char($c) => $body,
+            NCAttribute::Short($c) => $body,
+            NCAttribute::Int($c) => $body,
+            NCAttribute::Float($c) => $body,
+            NCAttribute::Double($c) => $body,
+            NCAttribute::UByte($c) => $body,
+            NCAttribute::UShort($c) => $body,
+            NCAttribute::UInt($c) => $body,
+            NCAttribute::Int64($c) => $body,
+            NCAttribute::UInt64($c) => $body,
+        }
+    };
+}
+
+impl NCAttribute {
+    /// Whether the attribute holds exactly one value.
+    pub fn is_scalar(&self) -> bool {
+        each_attribute!(self, c => c.values.len() == 1)
+    }
+
+    pub(crate) fn has_name(&self, name: &str) -> bool {
+        self.name() == name
+    }
+
+    /// The type of the attribute's values.
+    pub fn nc_type(&self) -> NCType {
+        match self {
+            NCAttribute::Byte(_) => NCType::Byte,
+            NCAttribute::Char(_) => NCType::Char,
+            NCAttribute::Short(_) => NCType::Short,
+            NCAttribute::Int(_) => NCType::Int,
+            NCAttribute::Float(_) => NCType::Float,
+            NCAttribute::Double(_) => NCType::Double,
+            NCAttribute::UByte(_) => NCType::UByte,
+            NCAttribute::UShort(_) => NCType::UShort,
+            NCAttribute::UInt(_) => NCType::UInt,
+            NCAttribute::Int64(_) => NCType::Int64,
+            NCAttribute::UInt64(_) => NCType::UInt64,
+        }
+    }
+
+    /// The attribute's name, whatever its type.
+    pub fn name(&self) -> &str {
+        each_attribute!(self, c => &c.name)
+    }
+
+    /// The text of a `Char` attribute.
+    pub(crate) fn as_text(&self) -> Option<String> {
+        match self {
+            NCAttribute::Char(c) => Some(c.to_string()),
+            _ => None,
+        }
+    }
+
+    /// The values of a numeric attribute widened to `f64`, or `None` for
+    /// `Char` attributes.
+    pub(crate) fn to_f64_vec(&self) -> Option<Vec<f64>> {
+        match self {
+            NCAttribute::Char(_) => None,
+            NCAttribute::Byte(c) => Some(c.values.iter().map(|v| v.to_f64()).collect()),
+            NCAttribute::Short(c) => Some(c.values.iter().map(|v| v.to_f64()).collect()),
+            NCAttribute::Int(c) => Some(c.values.iter().map(|v| v.to_f64()).collect()),
+            NCAttribute::Float(c) => Some(c.values.iter().map(|v| v.to_f64()).collect()),
+            NCAttribute::Double(c) => Some(c.values.clone()),
+            NCAttribute::UByte(c) => Some(c.values.iter().map(|v| v.to_f64()).collect()),
+            NCAttribute::UShort(c) => Some(c.values.iter().map(|v| v.to_f64()).collect()),
+            NCAttribute::UInt(c) => Some(c.values.iter().map(|v| v.to_f64()).collect()),
+            NCAttribute::Int64(c) => Some(c.values.iter().map(|v| v.to_f64()).collect()),
+            NCAttribute::UInt64(c) => Some(c.values.iter().map(|v| v.to_f64()).collect()),
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct NCAttributeContainer<T> {
     pub name: String,
     pub values: Vec<T>,
@@ -35,7 +231,7 @@ impl<T> NCAttributeContainer<T> {
     pub fn new(name: &str, values: Vec<T>) -> Self {
         Self {
             name: name.to_string(),
-            values: values,
+            values,
         }
     }
 }
@@ -66,6 +262,15 @@ impl fmt::Debug for NCAttributeContainer<u8> {
     }
 }
 
+impl fmt::Debug for NCAttributeContainer<i8> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NCAttributeContainer<i8>")
+            .field("name", &self.name)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
 impl fmt::Debug for NCAttributeContainer<i16> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("NCAttributeContainer<i16>")
@@ -102,28 +307,250 @@ impl fmt::Debug for NCAttributeContainer<f64> {
     }
 }
 
+impl fmt::Debug for NCAttributeContainer<u16> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NCAttributeContainer<u16>")
+            .field("name", &self.name)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl fmt::Debug for NCAttributeContainer<u32> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NCAttributeContainer<u32>")
+            .field("name", &self.name)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl fmt::Debug for NCAttributeContainer<i64> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NCAttributeContainer<i64>")
+            .field("name", &self.name)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl fmt::Debug for NCAttributeContainer<u64> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NCAttributeContainer<u64>")
+            .field("name", &self.name)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+/// A seekable byte source shared by lazily loaded variables.
+pub(crate) trait ReadSeek: io::Read + io::Seek + Send {}
+
+impl<R: io::Read + io::Seek + Send> ReadSeek for R {}
+
+/// Where to find data that hasn't been read yet.
+#[derive(Clone)]
+struct LazySource {
+    reader: Arc<Mutex<dyn ReadSeek>>,
+    extent: DataExtent,
+}
+
+impl LazySource {
+    fn read(&self) -> Result<Vec<u8>> {
+        let mut r = self.reader.lock().map_err(|_| ParseError::new("reader lock poisoned"))?;
+        self.extent.read(&mut *r, usize::MAX, false, None)
+    }
+
+    fn read_range(&self, start: usize, len: usize) -> Result<Vec<u8>> {
+        let mut r = self.reader.lock().map_err(|_| ParseError::new("reader lock poisoned"))?;
+        self.extent.read_range(&mut *r, start, len)
+    }
+}
+
+#[derive(Clone)]
 pub struct NCData<T> {
-    raw: Vec<u8>,
+    raw: OnceLock<Vec<u8>>,
+    source: Option<LazySource>,
+    pub(crate) partial: bool,
     _phantom: PhantomData<T>,
 }
 
 impl<T> NCData<T> {
     pub fn new(raw: Vec<u8>) -> Self {
         NCData {
-            raw,
+            raw: OnceLock::from(raw),
+            source: None,
+            partial: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Data stored at `extent` in `reader`, read on first access.
+    pub(crate) fn lazy(reader: Arc<Mutex<dyn ReadSeek>>, extent: DataExtent) -> Self {
+        NCData {
+            raw: OnceLock::new(),
+            source: Some(LazySource { reader, extent }),
+            partial: false,
             _phantom: PhantomData,
         }
     }
 
-    pub fn iter(&self) -> NCDataIter<T> {
-        NCDataIter::new(&self.raw)
+    /// Reads the data if it was opened lazily and hasn't been read yet.
+    /// Other accessors load lazily too, but see a failed read as empty
+    /// data; call this first to find out why a read failed. A failed read
+    /// isn't kept, so every call retries it.
+    pub fn load(&self) -> Result<()> {
+        if self.raw.get().is_none() {
+            if let Some(source) = &self.source {
+                let _ = self.raw.set(source.read()?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the data is in memory.
+    pub fn is_loaded(&self) -> bool {
+        self.raw.get().is_some()
+    }
+
+    fn loaded(&self) -> &Vec<u8> {
+        static EMPTY: Vec<u8> = Vec::new();
+
+        if let Some(raw) = self.raw.get() {
+            return raw;
+        }
+        match &self.source {
+            Some(source) => match source.read() {
+                Ok(raw) => self.raw.get_or_init(|| raw),
+                // leave the data unloaded so that `load` reports the failure
+                Err(_) => &EMPTY,
+            },
+            None => self.raw.get_or_init(Vec::new),
+        }
+    }
+
+    /// The stored big-endian bytes, without the padding that follows them
+    /// in the file.
+    pub(crate) fn raw(&self) -> &[u8] {
+        self.loaded()
+    }
+
+    /// `len` of the stored bytes beginning at byte `start`. Data that hasn't
+    /// been loaded yet is read from its source without loading the rest.
+    pub(crate) fn read_range(&self, start: usize, len: usize) -> Result<Vec<u8>> {
+        match (self.raw.get(), &self.source) {
+            (None, Some(source)) => source.read_range(start, len),
+            _ => start
+                .checked_add(len)
+                .and_then(|end| self.raw().get(start..end))
+                .map(<[u8]>::to_vec)
+                .ok_or(ParseError::UnexpectedEof),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn raw_mut(&mut self) -> &mut Vec<u8> {
+        self.loaded();
+        self.raw.get_or_init(Vec::new);
+        self.raw.get_mut().unwrap()
+    }
+
+    pub fn iter(&self) -> NCDataIter<'_, T> {
+        NCDataIter::new(self.raw())
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize
+    where
+        for<'a> NCDataIter<'a, T>: ExactSizeIterator,
+    {
+        self.iter().len()
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool
+    where
+        for<'a> NCDataIter<'a, T>: ExactSizeIterator,
+    {
+        self.len() == 0
+    }
+
+    /// The element at `index`, decoding only its bytes, or `None` if the
+    /// index is out of range.
+    pub fn get(&self, index: usize) -> Option<T>
+    where
+        for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+    {
+        self.iter().nth(index)
+    }
+
+    /// Whether only part of the variable's stored data was read.
+    pub fn is_partial(&self) -> bool {
+        self.partial
+    }
+}
+
+impl<T> NCData<T>
+where
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+{
+    /// Iterates over every `step`-th element, starting with the first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    pub fn iter_strided(&self, step: usize) -> impl Iterator<Item = T> + '_ {
+        assert!(step != 0, "stride must be non-zero");
+        self.iter().step_by(step)
+    }
+
+    /// Iterates over the values like `iter`, but yields an error instead of
+    /// silently stopping if the data can't be loaded or ends part way
+    /// through an element.
+    pub fn try_iter(&self) -> impl Iterator<Item = Result<T>> + '_ {
+        let mut failed = self.load().err();
+        let mut iter = self.iter();
+
+        iter::from_fn(move || {
+            if let Some(err) = failed.take() {
+                iter.pos = iter.end;
+                return Some(Err(err));
+            }
+
+            match iter.next() {
+                Some(v) => Some(Ok(v)),
+                None if iter.pos < iter.end => {
+                    iter.pos = iter.end;
+                    Some(Err(ParseError::UnexpectedEof))
+                }
+                None => None,
+            }
+        })
+    }
+
+    /// The decoded values. Unsigned bytes need no decoding, so `u8` data
+    /// borrows the raw buffer; other types are decoded into an owned vector.
+    pub fn as_cow(&self) -> Cow<'_, [T]>
+    where
+        T: Clone + 'static,
+    {
+        let raw = self.loaded();
+        if let Some(bytes) = (raw as &dyn Any).downcast_ref::<Vec<T>>() {
+            return Cow::Borrowed(bytes);
+        }
+
+        Cow::Owned(self.iter().collect())
     }
 }
 
 impl<T> fmt::Debug for NCData<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("NCData")
-            .field("data", &format!("[sequence of {} bytes]", self.raw.len()))
+            .field("data", &match self.raw.get() {
+                Some(raw) => format!("[sequence of {} bytes]", raw.len()),
+                None => String::from("[not loaded]"),
+            })
+            .field("partial", &self.partial)
             .finish()
     }
 }
@@ -132,6 +559,8 @@ impl<T> fmt::Debug for NCData<T> {
 pub struct NCDataIter<'a, T> {
     raw: &'a [u8],
     pos: usize,
+    /// One past the last byte not yet consumed from the back.
+    end: usize,
     _phantom: PhantomData<T>,
 }
 
@@ -140,22 +569,85 @@ impl<'a, T> NCDataIter<'a, T> {
         NCDataIter {
             raw,
             pos: 0,
+            end: raw.len(),
             _phantom: PhantomData,
         }
     }
 
-    fn check_pos(&self) -> Option<()> {
-        let size = std::mem::size_of::<T>();
-        if self.pos + size > self.raw.len() {
-            None
-        } else {
-            Some(())
+    fn check_pos(&self, size: usize) -> Option<()> {
+        match self.pos.checked_add(size) {
+            Some(end) if end <= self.end => Some(()),
+            _ => None,
+        }
+    }
+
+    fn increment_pos(&mut self, size: usize) {
+        self.pos += size;
+    }
+
+    /// Skips `n` elements without decoding them.
+    fn skip(&mut self, n: usize, size: usize) {
+        self.pos = self.pos.saturating_add(n.saturating_mul(size));
+    }
+
+    fn remaining(&self, size: usize) -> (usize, Option<usize>) {
+        let n = self.end.saturating_sub(self.pos) / size;
+        (n, Some(n))
+    }
+
+    /// Takes the last element not yet consumed from either end, returning
+    /// its position. Elements stay aligned to the front of the data, so any
+    /// trailing partial element is skipped.
+    fn take_back(&mut self, size: usize) -> Option<usize> {
+        let n = self.remaining(size).0;
+        if n == 0 {
+            return None;
         }
+
+        self.end = self.pos + (n - 1) * size;
+        Some(self.end)
+    }
+}
+
+impl<T> NCDataIter<'_, T>
+where
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+{
+    /// The number of bytes each element is stored in, which for `char`
+    /// differs from its size in memory.
+    pub(crate) fn element_size() -> usize {
+        const PROBE: [u8; 8] = [0; 8];
+        PROBE.len() / NCDataIter::<T>::new(&PROBE).size_hint().0
+    }
+}
+
+impl Iterator for NCDataIter<'_, i8> {
+    type Item = i8;
+
+    fn next(&mut self) -> Option<i8> {
+        self.check_pos(1)?;
+        let n = self.raw[self.pos] as i8;
+        self.increment_pos(1);
+
+        Some(n)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(1)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 1);
+        self.next()
     }
+}
 
-    fn increment_pos(&mut self) {
-        let size = std::mem::size_of::<T>();
-        self.pos = self.pos + size;
+impl ExactSizeIterator for NCDataIter<'_, i8> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, i8> {
+    fn next_back(&mut self) -> Option<i8> {
+        let at = self.take_back(1)?;
+        Some(self.raw[at] as i8)
     }
 }
 
@@ -163,149 +655,837 @@ impl Iterator for NCDataIter<'_, u8> {
     type Item = u8;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.check_pos()?;
+        self.check_pos(1)?;
         let n = self.raw[self.pos];
-        self.increment_pos();
+        self.increment_pos(1);
         
         Some(n)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(1)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 1);
+        self.next()
+    }
+}
+
+impl ExactSizeIterator for NCDataIter<'_, u8> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, u8> {
+    fn next_back(&mut self) -> Option<u8> {
+        let at = self.take_back(1)?;
+        Some(self.raw[at])
+    }
 }
 
 impl Iterator for NCDataIter<'_, char> {
     type Item = char;
 
     fn next(&mut self) -> Option<char> {
-        self.check_pos()?;
+        self.check_pos(1)?;
         let c = self.raw[self.pos] as char;
-        self.increment_pos();
+        self.increment_pos(1);
 
         Some(c)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(1)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 1);
+        self.next()
+    }
+}
+
+impl ExactSizeIterator for NCDataIter<'_, char> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, char> {
+    fn next_back(&mut self) -> Option<char> {
+        let at = self.take_back(1)?;
+        Some(self.raw[at] as char)
+    }
 }
 
 impl Iterator for NCDataIter<'_, i16> {
     type Item = i16;
 
     fn next(&mut self) -> Option<i16> {
-        self.check_pos()?;
+        self.check_pos(2)?;
         let buf: [u8; 2] = [self.raw[self.pos], self.raw[self.pos+1]];
-        self.increment_pos();
+        self.increment_pos(2);
         
         Some(i16::from_be_bytes(buf))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(2)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 2);
+        self.next()
+    }
+}
+
+impl ExactSizeIterator for NCDataIter<'_, i16> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, i16> {
+    fn next_back(&mut self) -> Option<i16> {
+        let at = self.take_back(2)?;
+        Some(i16::from_be_bytes(self.raw[at..at + 2].try_into().unwrap()))
+    }
 }
 
 impl Iterator for NCDataIter<'_, i32> {
     type Item = i32;
 
     fn next(&mut self) -> Option<i32> {
-        self.check_pos()?;
+        self.check_pos(4)?;
         let s = &self.raw[self.pos..self.pos+4];
         let buf: [u8; 4] = [s[0], s[1], s[2], s[3]];
-        self.increment_pos();
+        self.increment_pos(4);
         
         Some(i32::from_be_bytes(buf))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(4)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 4);
+        self.next()
+    }
+}
+
+impl ExactSizeIterator for NCDataIter<'_, i32> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, i32> {
+    fn next_back(&mut self) -> Option<i32> {
+        let at = self.take_back(4)?;
+        Some(i32::from_be_bytes(self.raw[at..at + 4].try_into().unwrap()))
+    }
 }
 
 impl Iterator for NCDataIter<'_, f32> {
     type Item = f32;
 
     fn next(&mut self) -> Option<f32> {
-        self.check_pos()?;
+        self.check_pos(4)?;
         let s = &self.raw[self.pos..self.pos+4];
         let buf: [u8; 4] = [s[0], s[1], s[2], s[3]];
-        self.increment_pos();
+        self.increment_pos(4);
         
         Some(f32::from_be_bytes(buf))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(4)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 4);
+        self.next()
+    }
+}
+
+impl ExactSizeIterator for NCDataIter<'_, f32> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, f32> {
+    fn next_back(&mut self) -> Option<f32> {
+        let at = self.take_back(4)?;
+        Some(f32::from_be_bytes(self.raw[at..at + 4].try_into().unwrap()))
+    }
 }
 
 impl Iterator for NCDataIter<'_, f64> {
     type Item = f64;
 
     fn next(&mut self) -> Option<f64> {
-        self.check_pos()?;
+        self.check_pos(8)?;
         let s = &self.raw[self.pos..self.pos+8];
         let buf: [u8; 8] = [s[0], s[1], s[2], s[3], s[4], s[5], s[6], s[7]];
-        self.increment_pos();
+        self.increment_pos(8);
 
         Some(f64::from_be_bytes(buf))
     }
-}
 
-#[derive(Debug)]
-pub enum NCVariable {
-    Byte(NCVariableContainer<u8>),
-    Char(NCVariableContainer<char>),
-    Short(NCVariableContainer<i16>),
-    Int(NCVariableContainer<i32>),
-    Float(NCVariableContainer<f32>),
-    Double(NCVariableContainer<f64>),
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(8)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 8);
+        self.next()
+    }
 }
 
-#[derive(Debug)]
-pub struct NCVariableContainer<T> {
-    pub name: String,
-    pub dimids: Vec<u32>,
-    pub attributes: Vec<NCAttribute>,
-    pub data: NCData<T>,
+impl ExactSizeIterator for NCDataIter<'_, f64> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, f64> {
+    fn next_back(&mut self) -> Option<f64> {
+        let at = self.take_back(8)?;
+        Some(f64::from_be_bytes(self.raw[at..at + 8].try_into().unwrap()))
+    }
 }
 
-#[derive(Debug)]
-pub struct ParseError {
-    reason: String,
+impl Iterator for NCDataIter<'_, u16> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        self.check_pos(2)?;
+        let s = &self.raw[self.pos..self.pos + 2];
+        self.increment_pos(2);
+
+        Some(u16::from_be_bytes(s.try_into().unwrap()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(2)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 2);
+        self.next()
+    }
 }
 
-impl ParseError {
-    pub fn new(reason: &str) -> Self {
-        Self {
-            reason: String::from(reason),
-        }
+impl ExactSizeIterator for NCDataIter<'_, u16> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, u16> {
+    fn next_back(&mut self) -> Option<u16> {
+        let at = self.take_back(2)?;
+        Some(u16::from_be_bytes(self.raw[at..at + 2].try_into().unwrap()))
     }
 }
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.reason)
+impl Iterator for NCDataIter<'_, u32> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        self.check_pos(4)?;
+        let s = &self.raw[self.pos..self.pos + 4];
+        self.increment_pos(4);
+
+        Some(u32::from_be_bytes(s.try_into().unwrap()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(4)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 4);
+        self.next()
     }
 }
 
-impl Error for ParseError {
-    fn description(&self) -> &str {
-        self.reason.as_str()
+impl ExactSizeIterator for NCDataIter<'_, u32> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, u32> {
+    fn next_back(&mut self) -> Option<u32> {
+        let at = self.take_back(4)?;
+        Some(u32::from_be_bytes(self.raw[at..at + 4].try_into().unwrap()))
     }
 }
 
-impl From<io::Error> for ParseError {
-    fn from(e: io::Error) -> Self {
-        Self {
-            reason: e.to_string(),
-        }
+impl Iterator for NCDataIter<'_, i64> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        self.check_pos(8)?;
+        let s = &self.raw[self.pos..self.pos + 8];
+        self.increment_pos(8);
+
+        Some(i64::from_be_bytes(s.try_into().unwrap()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(8)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 8);
+        self.next()
     }
 }
 
-impl From<FromUtf8Error> for ParseError {
-    fn from(e: FromUtf8Error) -> Self {
-        Self {
-            reason: e.to_string(),
-        }
+impl ExactSizeIterator for NCDataIter<'_, i64> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, i64> {
+    fn next_back(&mut self) -> Option<i64> {
+        let at = self.take_back(8)?;
+        Some(i64::from_be_bytes(self.raw[at..at + 8].try_into().unwrap()))
     }
 }
 
-type Result<T> = result::Result<T, ParseError>;
+impl Iterator for NCDataIter<'_, u64> {
+    type Item = u64;
 
-#[derive(Debug)]
-pub struct NCFile {
+    fn next(&mut self) -> Option<u64> {
+        self.check_pos(8)?;
+        let s = &self.raw[self.pos..self.pos + 8];
+        self.increment_pos(8);
+
+        Some(u64::from_be_bytes(s.try_into().unwrap()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining(8)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip(n, 8);
+        self.next()
+    }
+}
+
+impl ExactSizeIterator for NCDataIter<'_, u64> {}
+
+impl DoubleEndedIterator for NCDataIter<'_, u64> {
+    fn next_back(&mut self) -> Option<u64> {
+        let at = self.take_back(8)?;
+        Some(u64::from_be_bytes(self.raw[at..at + 8].try_into().unwrap()))
+    }
+}
+
+/// Numeric element types whose values can be widened to `f64`.
+pub trait NCNumeric: Copy {
+    fn to_f64(self) -> f64;
+
+    /// The fill value the netCDF libraries use for this type when a variable
+    /// has no `_FillValue` attribute.
+    fn default_fill() -> Self;
+
+    /// Reinterprets a widened value of this type as its unsigned counterpart,
+    /// as requested by the CF `_Unsigned` attribute. Only integer types are
+    /// affected.
+    fn reinterpret_unsigned(v: f64) -> f64 {
+        v
+    }
+}
+
+impl NCNumeric for i8 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_BYTE
+    }
+
+    fn reinterpret_unsigned(v: f64) -> f64 {
+        if v < 0.0 { v + 256.0 } else { v }
+    }
+}
+
+impl NCNumeric for i16 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_SHORT
+    }
+
+    fn reinterpret_unsigned(v: f64) -> f64 {
+        if v < 0.0 { v + 65536.0 } else { v }
+    }
+}
+
+impl NCNumeric for i32 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_INT
+    }
+
+    fn reinterpret_unsigned(v: f64) -> f64 {
+        if v < 0.0 { v + 4294967296.0 } else { v }
+    }
+}
+
+impl NCNumeric for f32 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_FLOAT
+    }
+}
+
+impl NCNumeric for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_DOUBLE
+    }
+}
+
+impl NCNumeric for u8 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_UBYTE
+    }
+}
+
+impl NCNumeric for u16 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_USHORT
+    }
+}
+
+impl NCNumeric for u32 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_UINT
+    }
+}
+
+impl NCNumeric for i64 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_INT64
+    }
+
+    fn reinterpret_unsigned(v: f64) -> f64 {
+        if v < 0.0 { v + 18446744073709551616.0 } else { v }
+    }
+}
+
+impl NCNumeric for u64 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn default_fill() -> Self {
+        DEFAULT_FILL_UINT64
+    }
+}
+
+/// Expands `$body` once for every arm of an `NCVariable`, binding the inner
+/// container to `$c`.
+macro_rules! each_variable {
+    ($var:expr, $c:ident => $body:expr) => {
+        match $var {
+            NCVariable::Byte($c) => $body,
+            NCVariable::Char($c) => $body,
+            NCVariable::Short($c) => $body,
+            NCVariable::Int($c) => $body,
+            NCVariable::Float($c) => $body,
+            NCVariable::Double($c) => $body,
+            NCVariable::UByte($c) => $body,
+            NCVariable::UShort($c) => $body,
+            NCVariable::UInt($c) => $body,
+            NCVariable::Int64($c) => $body,
+            NCVariable::UInt64($c) => $body,
+        }
+    };
+}
+
+#[derive(Debug, Clone)]
+pub enum NCVariable {
+    Byte(NCVariableContainer<i8>),
+    Char(NCVariableContainer<char>),
+    Short(NCVariableContainer<i16>),
+    Int(NCVariableContainer<i32>),
+    Float(NCVariableContainer<f32>),
+    Double(NCVariableContainer<f64>),
+    UByte(NCVariableContainer<u8>),
+    UShort(NCVariableContainer<u16>),
+    UInt(NCVariableContainer<u32>),
+    Int64(NCVariableContainer<i64>),
+    UInt64(NCVariableContainer<u64>),
+}
+
+/// Like `each_variable!`, but only expands `$body` for the numeric arms and
+/// evaluates `$other` for `Char`.
+macro_rules! each_numeric_variable {
+    ($var:expr, $c:ident => $body:expr, _ => $other:expr) => {
+        match $var {
+            NCVariable::Byte($c) => $body,
+            NCVariable::Short($c) => $body,
+            NCVariable::Int($c) => $body,
+            NCVariable::Float($c) => $body,
+            NCVariable::Double($c) => $body,
+            NCVariable::UByte($c) => $body,
+            NCVariable::UShort($c) => $body,
+            NCVariable::UInt($c) => $body,
+            NCVariable::Int64($c) => $body,
+            NCVariable::UInt64($c) => $body,
+            NCVariable::Char(_) => $other,
+        }
+    };
+}
+
+impl NCVariable {
+    /// The `(vsize, offset)` pair recorded in the variable's header.
+    pub(crate) fn layout(&self) -> (u64, u64) {
+        each_variable!(self, c => (c.vsize, c.offset))
+    }
+
+    pub(crate) fn dimids(&self) -> &[u32] {
+        each_variable!(self, c => &c.dimids)
+    }
+
+    /// Whether only part of the variable's stored data was read.
+    pub fn is_partial(&self) -> bool {
+        each_variable!(self, c => c.data.is_partial())
+    }
+
+    /// The type the variable's data is stored as.
+    pub fn nc_type(&self) -> NCType {
+        match self {
+            NCVariable::Byte(_) => NCType::Byte,
+            NCVariable::Char(_) => NCType::Char,
+            NCVariable::Short(_) => NCType::Short,
+            NCVariable::Int(_) => NCType::Int,
+            NCVariable::Float(_) => NCType::Float,
+            NCVariable::Double(_) => NCType::Double,
+            NCVariable::UByte(_) => NCType::UByte,
+            NCVariable::UShort(_) => NCType::UShort,
+            NCVariable::UInt(_) => NCType::UInt,
+            NCVariable::Int64(_) => NCType::Int64,
+            NCVariable::UInt64(_) => NCType::UInt64,
+        }
+    }
+
+    /// The variable's name, whatever its type.
+    pub fn name(&self) -> &str {
+        each_variable!(self, c => &c.name)
+    }
+
+    pub(crate) fn has_name(&self, name: &str) -> bool {
+        self.name() == name
+    }
+
+    pub(crate) fn attributes(&self) -> &[NCAttribute] {
+        each_variable!(self, c => &c.attributes)
+    }
+
+    pub(crate) fn attributes_mut(&mut self) -> &mut Vec<NCAttribute> {
+        each_variable!(self, c => &mut c.attributes)
+    }
+
+    /// Decodes the data of a numeric variable widened to `f64`, or `None` for
+    /// `Char` variables.
+    pub(crate) fn to_f64_vec(&self) -> Option<Vec<f64>> {
+        each_numeric_variable!(self, c => Some(c.widened().collect()), _ => None)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NCVariableContainer<T> {
+    pub name: String,
+    pub dimids: Vec<u32>,
+    pub attributes: Vec<NCAttribute>,
+    pub vsize: u64,
+    pub offset: u64,
+    pub data: NCData<T>,
+}
+
+impl<T> NCVariableContainer<T> {
+    /// The variable attribute called `name`.
+    pub fn attribute(&self, name: &str) -> Option<&NCAttribute> {
+        self.attributes.iter().find(|a| a.has_name(name))
+    }
+
+    /// The length of each of the variable's dimensions in `file`, in order,
+    /// with the unlimited dimension taking the current record count.
+    pub fn shape(&self, file: &NCFile) -> Vec<usize> {
+        file.resolve_shape(&self.dimids)
+    }
+}
+
+impl NCVariableContainer<char> {
+    /// The data decoded as text using the encoding named by the variable's
+    /// `_Encoding` attribute. Latin-1 (`ISO-8859-1`) maps each byte to the
+    /// code point of the same value; anything else, including a missing
+    /// attribute, is decoded as UTF-8 with invalid sequences replaced.
+    pub fn text(&self) -> String {
+        self.decode_text(self.data.raw())
+    }
+
+    /// Decodes `raw`, a prefix of this variable's data, as `text` does.
+    pub(crate) fn decode_text(&self, raw: &[u8]) -> String {
+        let encoding = self.attribute("_Encoding").and_then(NCAttribute::as_text);
+
+        match encoding.map(|e| e.trim_end_matches('\0').to_ascii_lowercase()).as_deref() {
+            Some("iso-8859-1" | "iso8859-1" | "latin-1" | "latin1") => {
+                raw.iter().map(|&b| char::from(b)).collect()
+            }
+            _ => String::from_utf8_lossy(raw).into_owned(),
+        }
+    }
+}
+
+impl NCVariableContainer<f64> {
+    /// The values as a fixed-size array, or `None` unless the variable
+    /// holds exactly `N` elements.
+    pub fn to_array<const N: usize>(&self) -> Option<[f64; N]> {
+        self.data.iter().collect::<Vec<_>>().try_into().ok()
+    }
+
+    /// The values of a `rows` × `cols` variable transposed from the stored
+    /// row-major order to column-major order, as expected by BLAS and
+    /// LAPACK. Returns `None` if `rows * cols` isn't the number of elements.
+    pub fn to_column_major(&self, rows: usize, cols: usize) -> Option<Vec<f64>> {
+        let values: Vec<f64> = self.data.iter().collect();
+        if rows.checked_mul(cols)? != values.len() {
+            return None;
+        }
+
+        Some(
+            (0..cols)
+                .flat_map(|c| (0..rows).map(move |r| r * cols + c))
+                .map(|i| values[i])
+                .collect(),
+        )
+    }
+}
+
+/// Why a file couldn't be read or an operation on it failed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The stream doesn't start with the `CDF` magic number.
+    BadMagic,
+    /// The stream starts with the HDF5 signature of a netCDF-4 file, a
+    /// format this crate doesn't read.
+    Netcdf4Unsupported,
+    /// A type code that isn't one of the `NC_*` types.
+    UnknownType(u8),
+    /// The stream ended before everything the header promised was read.
+    UnexpectedEof,
+    /// Any other I/O failure of the underlying reader or writer.
+    Io(io::Error),
+    /// A name or string that isn't valid UTF-8.
+    Utf8(FromUtf8Error),
+    /// Any other failure, described by a message.
+    Other(String),
+    /// `error`, which happened at byte `offset` of the stream.
+    At { offset: u64, error: Box<ParseError> },
+}
+
+impl ParseError {
+    /// An `Other` error with the message `reason`.
+    pub fn new(reason: &str) -> Self {
+        ParseError::Other(String::from(reason))
+    }
+
+    /// The error located at byte `offset`, unless it's already located.
+    pub(crate) fn at(self, offset: u64) -> Self {
+        match self {
+            ParseError::At { .. } => self,
+            error => ParseError::At { offset, error: Box::new(error) },
+        }
+    }
+
+    /// The byte offset in the stream where the error happened, if known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            ParseError::At { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// The kind of failure, looking past any location.
+    pub fn kind(&self) -> &ParseError {
+        match self {
+            ParseError::At { error, .. } => error.kind(),
+            _ => self,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::BadMagic => write!(f, "incorrect magic number"),
+            ParseError::Netcdf4Unsupported => write!(f, "netCDF-4 (HDF5) files are not supported"),
+            ParseError::UnknownType(code) => write!(f, "unknown type 0x{:02x}", code),
+            ParseError::UnexpectedEof => write!(f, "unexpected end of file"),
+            ParseError::Io(e) => write!(f, "{}", e),
+            ParseError::Utf8(e) => write!(f, "{}", e),
+            ParseError::Other(reason) => write!(f, "{}", reason),
+            ParseError::At { offset, error } => write!(f, "{} at offset {}", error, offset),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::Utf8(e) => Some(e),
+            ParseError::At { error, .. } => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => ParseError::UnexpectedEof,
+            _ => ParseError::Io(e),
+        }
+    }
+}
+
+impl From<FromUtf8Error> for ParseError {
+    fn from(e: FromUtf8Error) -> Self {
+        ParseError::Utf8(e)
+    }
+}
+
+pub(crate) type Result<T> = result::Result<T, ParseError>;
+
+/// A variable's header entry, read ahead of its data.
+struct VarEntry {
+    name: String,
+    dimids: Vec<u32>,
+    attributes: Vec<NCAttribute>,
+    nctype: u8,
+    /// Where the type code is stored, for error messages.
+    type_offset: u64,
+    vsize: u64,
+    offset: u64,
+}
+
+/// A failure while parsing a variable, split by whether the header entry was
+/// fully consumed before it happened.
+enum VarError {
+    Header(ParseError),
+    Data(ParseError),
+}
+
+impl From<ParseError> for VarError {
+    fn from(e: ParseError) -> Self {
+        VarError::Header(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NCFile {
     pub version: u8,
-    pub numrecs: u32,
+    pub numrecs: u64,
     pub dimensions: Vec<NCDimension>,
     pub attributes: Vec<NCAttribute>,
     pub variables: Vec<NCVariable>,
 }
 
+/// Knobs controlling how a file is read.
+#[derive(Debug, Default, Clone)]
+pub struct ReaderOptions {
+    /// Skip variables whose type or data can't be read instead of failing.
+    pub repair: bool,
+    /// Read at most this many elements of each variable's data.
+    pub max_elements: Option<usize>,
+    /// Give up with an error if reading runs past this instant. Checked
+    /// before each variable and each record is read.
+    pub deadline: Option<Instant>,
+    /// Keep whatever data is left of a variable cut short by the end of
+    /// the file, marking it partial, instead of failing.
+    pub allow_truncated: bool,
+}
+
 impl NCFile {
     pub fn new<R: io::Read + io::Seek>(r: &mut R) -> Result<Self> {
+        Ok(Self::parse(r, &ReaderOptions::default())?.0)
+    }
+
+    /// Opens the file at `path`, reading only its header. Each variable's
+    /// data is read from the file when it's first accessed.
+    pub fn open_lazy<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new_lazy(fs::File::open(path)?)
+    }
+
+    /// Reads only the header from `r`, keeping hold of the reader so that
+    /// each variable's data can be read when it's first accessed.
+    pub fn new_lazy<R: io::Read + io::Seek + Send + 'static>(mut r: R) -> Result<Self> {
+        let options = ReaderOptions {
+            max_elements: Some(0),
+            ..ReaderOptions::default()
+        };
+        let mut f = Self::parse(&mut r, &options)?.0;
+
+        let reader: Arc<Mutex<dyn ReadSeek>> = Arc::new(Mutex::new(r));
+        for i in 0..f.variables.len() {
+            let extent = f.data_extent(&f.variables[i]);
+            each_variable!(&mut f.variables[i], c => c.data = NCData::lazy(reader.clone(), extent));
+        }
+
+        Ok(f)
+    }
+
+    /// Reads a file the way `options` asks for.
+    pub fn with_options<R: io::Read + io::Seek>(r: &mut R, options: &ReaderOptions) -> Result<Self> {
+        Ok(Self::parse(r, options)?.0)
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut file = fs::File::open(path)?;
+        Self::new(&mut file)
+    }
+
+    /// Opens the file at `path`, skipping any variable whose type is unknown
+    /// or whose data can't be read rather than failing outright. Parsing
+    /// resumes at the end of the skipped variable's header entry, so the
+    /// variables around it are still read.
+    ///
+    /// Returns the file along with the number of variables skipped.
+    pub fn open_repair<P: AsRef<Path>>(path: P) -> Result<(Self, usize)> {
+        let mut file = fs::File::open(path)?;
+        let options = ReaderOptions {
+            repair: true,
+            ..ReaderOptions::default()
+        };
+        Self::parse(&mut file, &options)
+    }
+
+    /// Opens the file at `path`, reading at most `max_elems` elements of each
+    /// variable's data. Variables holding more than that are marked partial.
+    pub fn open_sampled<P: AsRef<Path>>(path: P, max_elems: usize) -> Result<Self> {
+        let mut file = fs::File::open(path)?;
+        let options = ReaderOptions {
+            max_elements: Some(max_elems),
+            ..ReaderOptions::default()
+        };
+        Ok(Self::parse(&mut file, &options)?.0)
+    }
+
+    /// Parses a file, returning it with the number of variables skipped.
+    /// Errors reading a dimension, attribute or variable are located at the
+    /// offset of the field that couldn't be read.
+    pub(crate) fn parse<R: io::Read + io::Seek>(
+        r: &mut R,
+        options: &ReaderOptions,
+    ) -> Result<(Self, usize)> {
+        let mut skipped = 0;
         let mut f = Self {
             version: 0,
             numrecs: 0,
@@ -314,57 +1494,173 @@ impl NCFile {
             variables: Vec::new(),
         };
 
-        f.validate_magic_number(r)?;
+        Self::validate_magic_number(r)?;
         f.version = read_u8(r)?;
-        f.numrecs = read_u32(r)?;
+        f.numrecs = read_non_neg(r, f.version)?;
 
         let dimflag = read_u32(r)? as u8;
         if dimflag == NC_DIMENSION {
             f.dimensions = f.parse_dimlist(r)?;
         } else {
-            // advance 4 bytes
-            r.seek(io::SeekFrom::Current(4))?;
+            // skip the zero count
+            read_non_neg(r, f.version)?;
         }
 
         let attrflag = read_u32(r)? as u8;
         if attrflag == NC_ATTRIBUTE {
             f.attributes = f.parse_attrlist(r)?;
         } else {
-            // advance 4 bytes
-            r.seek(io::SeekFrom::Current(4))?;
+            // skip the zero count
+            read_non_neg(r, f.version)?;
         }
 
         let varflag = read_u32(r)? as u8;
         if varflag == NC_VARIABLE {
-            f.variables = f.parse_varlist(r)?;
+            let (variables, n) = f.parse_varlist(r, options)?;
+            f.variables = variables;
+            skipped = n;
         } else {
-            // advance 4 bytes
-            r.seek(io::SeekFrom::Current(4))?;
+            // skip the zero count
+            read_non_neg(r, f.version)?;
         }
 
-        Ok(f)
+        let streaming = if f.version == VERSION_CDF5 { STREAMING_64 } else { u64::from(STREAMING) };
+        if f.numrecs == streaming {
+            f.numrecs = f.count_streamed_records(r)?;
+        }
+        f.read_records(r, options)?;
+
+        Ok((f, skipped))
     }
 
-    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let mut file = fs::File::open(path)?;
-        Self::new(&mut file)
+    /// Reads the data of every record variable. Records interleave the
+    /// data of all record variables, so this can only happen once the whole
+    /// header is known.
+    fn read_records<R: io::Read + io::Seek>(&mut self, r: &mut R, options: &ReaderOptions) -> Result<()> {
+        for i in 0..self.variables.len() {
+            let var = &self.variables[i];
+            if !self.is_record_variable(var) {
+                continue;
+            }
+
+            let extent = self.data_extent(var);
+            let size = var.nc_type().size();
+            let limit = options.max_elements.map_or(usize::MAX, |max| max.saturating_mul(size));
+            let raw = extent
+                .read(r, limit, options.allow_truncated, options.deadline)
+                .map_err(|e| e.at(extent.offset))?;
+            let partial = raw.len() < extent.total();
+
+            each_variable!(&mut self.variables[i], c => {
+                c.data = NCData::new(raw);
+                c.data.partial = partial;
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Works out how many records a streamed file holds, as its header
+    /// can't say, from the space left after the start of the record data.
+    fn count_streamed_records<R: io::Seek>(&self, r: &mut R) -> Result<u64> {
+        let record_size = self.record_size();
+        let start = self.record_variables().iter().map(|v| v.layout().1).min();
+
+        match start {
+            Some(start) if record_size > 0 => {
+                let end = r.seek(io::SeekFrom::End(0))?;
+                Ok(end.saturating_sub(start) / record_size)
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Resolves dimension ids into their lengths, substituting `numrecs` for
+    /// the unlimited dimension.
+    pub(crate) fn resolve_shape(&self, dimids: &[u32]) -> Vec<usize> {
+        dimids
+            .iter()
+            .map(|&id| match self.dimension_by_id(id) {
+                Some(d) if d.length == 0 => self.numrecs as usize,
+                Some(d) => d.length as usize,
+                None => 0,
+            })
+            .collect()
+    }
+
+    /// The dimension called `name`.
+    pub fn dimension(&self, name: &str) -> Option<&NCDimension> {
+        self.dimensions.iter().find(|d| d.name == name)
+    }
+
+    /// The dimension with id `id`. Dimension ids, as stored in a variable's
+    /// `dimids`, are positions in `dimensions`, which keeps the order of the
+    /// file header.
+    pub fn dimension_by_id(&self, id: u32) -> Option<&NCDimension> {
+        self.dimensions.get(id as usize)
+    }
+
+    /// The variable called `name`, whatever its type.
+    pub fn variable(&self, name: &str) -> Option<&NCVariable> {
+        self.variables.iter().find(|v| v.has_name(name))
+    }
+
+    /// A mutable reference to the variable called `name`.
+    pub fn variable_mut(&mut self, name: &str) -> Option<&mut NCVariable> {
+        self.variables.iter_mut().find(|v| v.has_name(name))
+    }
+
+    /// The variables whose names match the glob `pattern`, where `*` matches
+    /// any run of characters and `?` matches any single character.
+    pub fn variables_matching(&self, pattern: &str) -> Vec<&NCVariable> {
+        let pattern: Vec<char> = pattern.chars().collect();
+
+        self.variables
+            .iter()
+            .filter(|v| glob_match(&pattern, &v.name().chars().collect::<Vec<_>>()))
+            .collect()
+    }
+
+    /// The global attribute called `name`.
+    pub fn attribute(&self, name: &str) -> Option<&NCAttribute> {
+        self.attributes.iter().find(|a| a.has_name(name))
+    }
+
+    /// Whether the variable's slowest varying dimension is the unlimited
+    /// dimension, meaning its data is stored interleaved by record.
+    pub fn is_record_variable(&self, var: &NCVariable) -> bool {
+        match var.dimids().first() {
+            Some(&id) => self.dimension_by_id(id).is_some_and(|d| d.length == 0),
+            None => false,
+        }
+    }
+
+    /// The record variables, in header order.
+    pub fn record_variables(&self) -> Vec<&NCVariable> {
+        self.variables.iter().filter(|v| self.is_record_variable(v)).collect()
     }
 
-    fn validate_magic_number<R: io::Read>(&self, r: &mut R) -> Result<()> {
+    pub(crate) fn validate_magic_number<R: io::Read>(r: &mut R) -> Result<()> {
         let mut buf: [u8; 3] = [0; 3];
-        
-        r.read_exact(&mut buf).unwrap();
-        let magic = String::from_utf8(buf.to_vec())?;
 
-        if magic != MAGIC_NUMBER {
-            Err(ParseError::new("incorrect magic number"))
-        } else {
-            Ok(())
+        r.read_exact(&mut buf)?;
+        if buf == MAGIC_NUMBER.as_bytes() {
+            return Ok(());
+        }
+
+        // netCDF-4 files are HDF5 files, which have a longer signature
+        if buf == HDF5_SIGNATURE[..3] {
+            let mut rest = [0; 5];
+            if r.read_exact(&mut rest).is_ok() && rest == HDF5_SIGNATURE[3..] {
+                return Err(ParseError::Netcdf4Unsupported);
+            }
         }
+
+        Err(ParseError::BadMagic)
     }
 
-    fn parse_dimlist<R: io::Read>(&self, r: &mut R) -> Result<Vec<NCDimension>> {
-        let len = read_u32(r)?;
+    fn parse_dimlist<R: io::Read + io::Seek>(&self, r: &mut R) -> Result<Vec<NCDimension>> {
+        let len = read_field(r, |r| read_non_neg(r, self.version))?;
         let mut dimlist: Vec<NCDimension> = Vec::new();
 
         for _ in 0..len {
@@ -374,9 +1670,9 @@ impl NCFile {
         Ok(dimlist)
     }
 
-    fn parse_dim<R: io::Read>(&self, r: &mut R) -> Result<NCDimension> {
-        let name = read_string(r)?;
-        let dimlen = read_u32(r)?;
+    fn parse_dim<R: io::Read + io::Seek>(&self, r: &mut R) -> Result<NCDimension> {
+        let name = read_field(r, |r| read_string(r, self.version))?;
+        let dimlen = read_field(r, |r| read_non_neg(r, self.version))?;
 
         Ok(NCDimension {
             name,
@@ -384,8 +1680,8 @@ impl NCFile {
         })
     }
 
-    fn parse_attrlist<R: io::Read>(&self, r: &mut R) -> Result<Vec<NCAttribute>> {
-        let len = read_u32(r)?;
+    fn parse_attrlist<R: io::Read + io::Seek>(&self, r: &mut R) -> Result<Vec<NCAttribute>> {
+        let len = read_field(r, |r| read_non_neg(r, self.version))?;
         let mut attrlist: Vec<NCAttribute> = Vec::new();
 
         for _ in 0..len {
@@ -395,167 +1691,324 @@ impl NCFile {
         Ok(attrlist)
     }
 
-    fn parse_attr<R: io::Read>(&self, r: &mut R) -> Result<NCAttribute> {
-        let name = &read_string(r)?;
-        let nctype = read_u32(r)? as u8;
+    fn parse_attr<R: io::Read + io::Seek>(&self, r: &mut R) -> Result<NCAttribute> {
+        let name = read_field(r, |r| read_string(r, self.version))?;
+        let type_offset = r.stream_position()?;
+        let nctype = read_u32(r).map_err(|e| e.at(type_offset))? as u8;
+        if nc_type_size(nctype).is_none() {
+            return Err(ParseError::UnknownType(nctype).at(type_offset));
+        }
+
+        read_field(r, |r| self.parse_attr_values(r, &name, nctype))
+    }
 
+    /// Reads the values of an attribute of the known type `nctype`.
+    fn parse_attr_values<R: io::Read>(
+        &self,
+        r: &mut R,
+        name: &str,
+        nctype: u8,
+    ) -> Result<NCAttribute> {
         Ok(match nctype {
             NC_BYTE => {
-                let len = read_u32(r)? as usize;
+                let len = to_usize(read_non_neg(r, self.version)?)?;
 
                 NCAttribute::Byte(
-                    NCAttributeContainer::new(name, read_bytes(r, len)?)
+                    NCAttributeContainer::new(name, read_values(r, len, 1)?)
                 )
             },
             NC_CHAR => {
-                let s = read_string(r)?;
+                let s = read_text(r, self.version)?;
 
                 NCAttribute::Char(
                     NCAttributeContainer::new(name, s.chars().collect())
                 )
             },
             NC_SHORT => {
-                let len = read_u32(r)? as usize;
+                let len = to_usize(read_non_neg(r, self.version)?)?;
 
                 NCAttribute::Short(
                     NCAttributeContainer::new(name, read_i16_padded_list(r, len)?)
                 )
             },
             NC_INT => {
-                let len = read_u32(r)? as usize;
+                let len = to_usize(read_non_neg(r, self.version)?)?;
 
                 NCAttribute::Int(
                     NCAttributeContainer::new(name, read_i32_list(r, len)?)
                 )
             },
             NC_FLOAT => {
-                let len = read_u32(r)? as usize;
+                let len = to_usize(read_non_neg(r, self.version)?)?;
 
                 NCAttribute::Float(
                     NCAttributeContainer::new(name, read_f32_list(r, len)?)
                 )
             },
             NC_DOUBLE => {
-                let len = read_u32(r)? as usize;
+                let len = to_usize(read_non_neg(r, self.version)?)?;
 
                 NCAttribute::Double(
                     NCAttributeContainer::new(name, read_f64_list(r, len)?)
                 )
-            }
+            },
+            NC_UBYTE => {
+                let len = to_usize(read_non_neg(r, self.version)?)?;
 
-            _ => return Err(ParseError::new("unknown type")),
+                NCAttribute::UByte(
+                    NCAttributeContainer::new(name, read_values(r, len, 1)?)
+                )
+            },
+            NC_USHORT => {
+                let len = to_usize(read_non_neg(r, self.version)?)?;
+
+                NCAttribute::UShort(
+                    NCAttributeContainer::new(name, read_values(r, len, 2)?)
+                )
+            },
+            NC_UINT => {
+                let len = to_usize(read_non_neg(r, self.version)?)?;
+
+                NCAttribute::UInt(
+                    NCAttributeContainer::new(name, read_values(r, len, 4)?)
+                )
+            },
+            NC_INT64 => {
+                let len = to_usize(read_non_neg(r, self.version)?)?;
+
+                NCAttribute::Int64(
+                    NCAttributeContainer::new(name, read_values(r, len, 8)?)
+                )
+            },
+            NC_UINT64 => {
+                let len = to_usize(read_non_neg(r, self.version)?)?;
+
+                NCAttribute::UInt64(
+                    NCAttributeContainer::new(name, read_values(r, len, 8)?)
+                )
+            },
+
+            _ => return Err(ParseError::UnknownType(nctype)),
         })
     }
 
-    fn parse_varlist<R: io::Read + io::Seek>(&self, r: &mut R) -> Result<Vec<NCVariable>> {
-        let len = read_u32(r)?;
+    fn parse_varlist<R: io::Read + io::Seek>(
+        &self,
+        r: &mut R,
+        options: &ReaderOptions,
+    ) -> Result<(Vec<NCVariable>, usize)> {
+        let len = read_field(r, |r| read_non_neg(r, self.version))?;
         let mut varlist: Vec<NCVariable> = Vec::new();
+        let mut skipped = 0;
 
         for _ in 0..len {
-            varlist.push(self.parse_var(r)?);
+            check_deadline(options.deadline)?;
+
+            match self.parse_var(r, options) {
+                Ok(var) => varlist.push(var),
+                Err(VarError::Data(_)) if options.repair => skipped += 1,
+                Err(VarError::Data(e)) | Err(VarError::Header(e)) => return Err(e),
+            }
         }
 
-        Ok(varlist)
+        Ok((varlist, skipped))
     }
 
-    fn parse_var<R: io::Read + io::Seek>(&self, r: &mut R) -> Result<NCVariable> {
-        let name = read_string(r)?;
-        let dimlen = read_u32(r)?;
+    fn parse_var<R: io::Read + io::Seek>(
+        &self,
+        r: &mut R,
+        options: &ReaderOptions,
+    ) -> result::Result<NCVariable, VarError> {
+        let name = read_field(r, |r| read_string(r, self.version))?;
+        let dimlen = read_field(r, |r| read_non_neg(r, self.version))?;
         let mut dimids = Vec::new();
         
         for _ in 0..dimlen {
-            dimids.push(read_u32(r)?);
+            dimids.push(read_field(r, |r| read_non_neg(r, self.version))? as u32);
         }
 
         // next byte is attr flag
-        r.seek(io::SeekFrom::Current(4))?;
+        r.seek(io::SeekFrom::Current(4)).map_err(ParseError::from)?;
         let attributes = self.parse_attrlist(r)?;
 
-        let nctype = read_u32(r)? as u8;
-        let vsize = read_u32(r)? as usize;
-        let offset = if self.version == 0x1 {
-            read_u32(r)? as u64
-        } else {
-            read_u64(r)?
+        let type_offset = r.stream_position().map_err(ParseError::from)?;
+        let nctype = read_u32(r).map_err(|e| e.at(type_offset))? as u8;
+        let vsize = read_field(r, |r| read_non_neg(r, self.version))?;
+        let offset = read_field(r, |r| {
+            if self.version == 0x1 {
+                read_u32(r).map(u64::from)
+            } else {
+                read_u64(r)
+            }
+        })?;
+
+        let entry = VarEntry {
+            name,
+            dimids,
+            attributes,
+            nctype,
+            type_offset,
+            vsize,
+            offset,
         };
 
         // keep track of the old stream position
-        let was = r.seek(io::SeekFrom::Current(0))?;
+        let was = r.stream_position().map_err(ParseError::from)?;
+        // the header entry has been consumed, so failures past this point
+        // leave the stream aligned on the next variable
+        let var = self.read_var(r, entry, options);
+
+        // seek back to end of variable def
+        r.seek(io::SeekFrom::Start(was)).map_err(ParseError::from)?;
+
+        // errors reading the data are located at its start
+        var.map_err(|e| VarError::Data(e.at(offset)))
+    }
+
+    fn read_var<R: io::Read + io::Seek>(
+        &self,
+        r: &mut R,
+        entry: VarEntry,
+        options: &ReaderOptions,
+    ) -> Result<NCVariable> {
+        let VarEntry { name, dimids, attributes, nctype, type_offset, vsize, offset } = entry;
+
+        // record data is read once the whole header is known
+        let is_record = dimids
+            .first()
+            .and_then(|&id| self.dimension_by_id(id))
+            .is_some_and(|d| d.length == 0);
+
+        // the padding after the last element isn't part of the data
+        let needed = match nc_type_size(nctype) {
+            Some(size) => self
+                .resolve_shape(&dimids)
+                .iter()
+                .fold(size, |n, &len| n.saturating_mul(len)),
+            None => usize::MAX,
+        };
+        let mut len = if is_record { 0 } else { to_usize(vsize)?.min(needed) };
+        if let (Some(max), Some(size)) = (options.max_elements, nc_type_size(nctype)) {
+            len = len.min(max.saturating_mul(size));
+        }
+
         // seek to offset
         r.seek(io::SeekFrom::Start(offset))?;
 
-        let data = read_bytes(r, vsize)?;
-        let var = match nctype {
-            NC_BYTE => NCVariable::Byte(NCVariableContainer::<u8> {
+        let data = if options.allow_truncated {
+            use std::io::Read;
+
+            let mut buf = Vec::new();
+            r.by_ref().take(len as u64).read_to_end(&mut buf)?;
+            buf
+        } else {
+            read_bytes(r, len)?
+        };
+        let partial = data.len() < needed;
+        let mut var = match nctype {
+            NC_BYTE => NCVariable::Byte(NCVariableContainer::<i8> {
                 name,
                 dimids,
                 attributes,
+                vsize,
+                offset,
                 data: NCData::new(data),
             }),
             NC_CHAR => NCVariable::Char(NCVariableContainer::<char> {
                 name,
                 dimids,
                 attributes,
+                vsize,
+                offset,
                 data: NCData::new(data),
             }),
             NC_SHORT => NCVariable::Short(NCVariableContainer::<i16> {
                 name,
                 dimids,
                 attributes,
+                vsize,
+                offset,
                 data: NCData::new(data),
             }),
             NC_INT => NCVariable::Int(NCVariableContainer::<i32> {
                 name,
                 dimids,
                 attributes,
+                vsize,
+                offset,
                 data: NCData::new(data),
             }),
             NC_FLOAT => NCVariable::Float(NCVariableContainer::<f32> {
                 name,
                 dimids,
                 attributes,
+                vsize,
+                offset,
                 data: NCData::new(data),
             }),
             NC_DOUBLE => NCVariable::Double(NCVariableContainer::<f64> {
                 name,
                 dimids,
                 attributes,
+                vsize,
+                offset,
+                data: NCData::new(data),
+            }),
+            NC_UBYTE => NCVariable::UByte(NCVariableContainer::<u8> {
+                name,
+                dimids,
+                attributes,
+                vsize,
+                offset,
+                data: NCData::new(data),
+            }),
+            NC_USHORT => NCVariable::UShort(NCVariableContainer::<u16> {
+                name,
+                dimids,
+                attributes,
+                vsize,
+                offset,
+                data: NCData::new(data),
+            }),
+            NC_UINT => NCVariable::UInt(NCVariableContainer::<u32> {
+                name,
+                dimids,
+                attributes,
+                vsize,
+                offset,
+                data: NCData::new(data),
+            }),
+            NC_INT64 => NCVariable::Int64(NCVariableContainer::<i64> {
+                name,
+                dimids,
+                attributes,
+                vsize,
+                offset,
+                data: NCData::new(data),
+            }),
+            NC_UINT64 => NCVariable::UInt64(NCVariableContainer::<u64> {
+                name,
+                dimids,
+                attributes,
+                vsize,
+                offset,
                 data: NCData::new(data),
             }),
 
-            _ => return Err(ParseError::new("unknown type")),
+            _ => return Err(ParseError::UnknownType(nctype).at(type_offset)),
         };
 
-        // seek back to end of variable def
-        r.seek(io::SeekFrom::Start(was))?;
-
+        each_variable!(&mut var, c => c.data.partial = partial);
         Ok(var)
     }
 }
 
-fn read_u8<R: io::Read>(r: &mut R) -> Result<u8> {
+pub(crate) fn read_u8<R: io::Read>(r: &mut R) -> Result<u8> {
     let mut buf: [u8; 1] = [0; 1];
     r.read_exact(&mut buf)?;
     Ok(buf[0])
 }
 
-fn read_i16<R: io::Read>(r: &mut R) -> Result<i16> {
-    let mut buf: [u8; 2] = [0; 2];
-    r.read_exact(&mut buf)?;
-    Ok(i16::from_be_bytes(buf))
-}
-
-fn read_i16_list<R: io::Read>(r: &mut R, len: usize) -> Result<Vec<i16>> {
-    let mut vals = Vec::new();
-
-    for _ in 0..len {
-        let v = read_i16(r)?;
-        vals.push(v);
-    }
-
-    Ok(vals)
-}
-
 fn read_i16_padded<R: io::Read>(r: &mut R) -> Result<i16> {
     let raw = read_bytes_padded(r, 2)?;
     let buf: [u8; 2] = [raw[0], raw[1]];
@@ -573,7 +2026,7 @@ fn read_i16_padded_list<R: io::Read>(r: &mut R, len: usize) -> Result<Vec<i16>>
     Ok(vals)
 }
 
-fn read_u32<R: io::Read>(r: &mut R) -> Result<u32> {
+pub(crate) fn read_u32<R: io::Read>(r: &mut R) -> Result<u32> {
     let mut buf: [u8; 4] = [0; 4];
     r.read_exact(&mut buf)?;
     Ok(u32::from_be_bytes(buf))
@@ -595,7 +2048,7 @@ fn read_i32_list<R: io::Read>(r: &mut R, len: usize) -> Result<Vec<i32>> {
     Ok(vals)
 }
 
-fn read_u64<R: io::Read>(r: &mut R) -> Result<u64> {
+pub(crate) fn read_u64<R: io::Read>(r: &mut R) -> Result<u64> {
     let mut buf: [u8; 8] = [0; 8];
     r.read_exact(&mut buf)?;
     Ok(u64::from_be_bytes(buf))
@@ -633,37 +2086,155 @@ fn read_f64_list<R: io::Read>(r: &mut R, len: usize) -> Result<Vec<f64>> {
     Ok(vals)
 }
 
-fn read_bytes_padded<R: io::Read>(r: &mut R, len: usize) -> Result<Vec<u8>> {
-    // string length is rounded to the nearest 4 bytes
-    let buflen = if len % 4 == 0 {
+/// Fails if `deadline` has passed.
+pub(crate) fn check_deadline(deadline: Option<Instant>) -> Result<()> {
+    if deadline.is_some_and(|d| Instant::now() > d) {
+        return Err(ParseError::new("read deadline exceeded"));
+    }
+    Ok(())
+}
+
+/// Reads a header field with `read`, locating any error at the offset the
+/// field starts at.
+fn read_field<R, T, F>(r: &mut R, read: F) -> Result<T>
+where
+    R: io::Seek,
+    F: FnOnce(&mut R) -> Result<T>,
+{
+    let offset = r.stream_position()?;
+    read(r).map_err(|e| e.at(offset))
+}
+
+/// Reads `len` big-endian values of `size` bytes each, followed by padding
+/// to the next 4-byte boundary.
+fn read_values<R, T>(r: &mut R, len: usize, size: usize) -> Result<Vec<T>>
+where
+    R: io::Read,
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+{
+    let bytes = len
+        .checked_mul(size)
+        .ok_or_else(|| ParseError::new(&format!("{} values of {} bytes are too many", len, size)))?;
+    let buf = read_bytes_padded(r, bytes)?;
+    Ok(NCDataIter::new(&buf).take(len).collect())
+}
+
+/// Matches `name` against a glob `pattern`, backtracking only to the most
+/// recent `*`, so it takes at most `pattern.len() * name.len()` steps.
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // the position just past the last `*`, and the name position it matched up to
+    let mut star = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                star = Some((p, n));
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // let the last `*` swallow one more character
+                Some((sp, sn)) => {
+                    p = sp;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Size in bytes of a single element of the given type.
+pub(crate) fn nc_type_size(nc_type: u8) -> Option<usize> {
+    NCType::from_code(nc_type).map(NCType::size)
+}
+
+/// Rounds a length up to the nearest 4 bytes.
+pub(crate) fn padded_len(len: usize) -> usize {
+    if len % 4 == 0 {
         len
     } else {
         len + (4 - (len % 4))
-    };
+    }
+}
+
+/// Rounds a length read from a header up to the nearest 4 bytes, failing
+/// if that overflows.
+pub(crate) fn checked_padded_len(len: usize) -> Result<usize> {
+    len.checked_add(3)
+        .map(|n| n & !3)
+        .ok_or_else(|| ParseError::new(&format!("length {} is too large", len)))
+}
+
+/// Converts a count or length read from a header to `usize`, failing if it
+/// doesn't fit.
+pub(crate) fn to_usize(n: u64) -> Result<usize> {
+    usize::try_from(n).map_err(|_| ParseError::new(&format!("length {} is too large", n)))
+}
+
+fn read_bytes_padded<R: io::Read>(r: &mut R, len: usize) -> Result<Vec<u8>> {
+    use std::io::Read;
+
+    // string length is rounded to the nearest 4 bytes
+    let padded = checked_padded_len(len)?;
+
+    // the length comes from the header, so don't trust it with an
+    // allocation up front: the buffer only grows as bytes arrive
+    let mut buf = Vec::new();
+    r.take(padded as u64).read_to_end(&mut buf)?;
+    if buf.len() < padded {
+        return Err(ParseError::UnexpectedEof);
+    }
 
-    let mut buf = vec![0; buflen];
-    r.read_exact(&mut buf)?;
     Ok(buf)
 }
 
 fn read_bytes<R: io::Read>(r: &mut R, len: usize) -> Result<Vec<u8>> {
-    let buf = read_bytes_padded(r, len)?;
-    Ok(buf[..len].to_vec())
+    let mut buf = read_bytes_padded(r, len)?;
+    buf.truncate(len);
+    Ok(buf)
+}
+
+/// Reads a count or length, which CDF-5 widens to 64 bits.
+pub(crate) fn read_non_neg<R: io::Read>(r: &mut R, version: u8) -> Result<u64> {
+    if version == VERSION_CDF5 {
+        read_u64(r)
+    } else {
+        read_u32(r).map(u64::from)
+    }
 }
 
-fn read_string<R: io::Read>(r: &mut R) -> Result<String> {
-    let len = read_u32(r)? as usize;
+pub(crate) fn read_string<R: io::Read>(r: &mut R, version: u8) -> Result<String> {
+    let len = to_usize(read_non_neg(r, version)?)?;
     let strbuf = read_bytes(r, len)?;
     Ok(String::from_utf8(strbuf)?)
 }
 
+/// Reads text such as a char attribute's value. Unlike names, values aren't
+/// required to be UTF-8, so bytes that aren't are read as Latin-1, mapping
+/// each byte to the code point of the same value.
+pub(crate) fn read_text<R: io::Read>(r: &mut R, version: u8) -> Result<String> {
+    let len = to_usize(read_non_neg(r, version)?)?;
+    let buf = read_bytes(r, len)?;
+    Ok(String::from_utf8(buf)
+        .unwrap_or_else(|e| e.into_bytes().into_iter().map(char::from).collect()))
+}
+
 #[cfg(test)]
 mod test {
     use std::fs;
     use super::*;
 
-    const SAMPLE_FILE_1: &'static str = "./samples/sample1.nc";
-    const SAMPLE_FILE_2: &'static str = "./samples/sample2.nc";
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+    const SAMPLE_FILE_2: &str = "./samples/sample2.nc";
+    const SAMPLE_FILE_3: &str = "./samples/sample3.nc";
 
     fn open_sample1() -> NCFile {
         NCFile::open(SAMPLE_FILE_1).unwrap()
@@ -728,4 +2299,787 @@ mod test {
             panic!("first variable isn't Float");
         }
     }
+
+    /// Returns the offset of the corrupted type code.
+    fn corrupt_second_variable_type(bytes: &mut [u8]) -> usize {
+        // latitude is a float (type 5) with a vsize of 1600 bytes
+        let entry = [0, 0, 0, 5, 0, 0, 0x06, 0x40];
+        let pos = bytes.windows(8).position(|w| w == entry).unwrap();
+        bytes[pos + 3] = 0x0c;
+        pos
+    }
+
+    #[test]
+    fn it_repairs_corrupt_variables() {
+        let mut bytes = fs::read(SAMPLE_FILE_1).unwrap();
+        corrupt_second_variable_type(&mut bytes);
+        let path = crate::test_util::TempPath::new("corrupt.nc");
+        fs::write(&path, &bytes).unwrap();
+
+        assert!(NCFile::open(&path).is_err());
+
+        let (f, skipped) = NCFile::open_repair(&path).unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(f.variables.len(), 3);
+        assert!(f.variables[0].has_name("longitude"));
+        assert!(f.variables[1].has_name("time"));
+        if let NCVariable::Int(t) = &f.variables[1] {
+            assert_eq!(t.data.iter().count(), 25);
+        } else {
+            panic!("time isn't Int");
+        }
+    }
+
+    #[test]
+    fn it_iterates_with_a_stride() {
+        let f = open_sample1();
+
+        if let NCVariable::Float(n) = &f.variables[0] {
+            let values: Vec<f32> = n.data.iter_strided(2).take(3).collect();
+            assert_eq!(values, vec![-24.95, -24.75, -24.55]);
+            assert_eq!(n.data.iter_strided(2).count(), 350);
+            assert!(n.data.iter_strided(1).eq(n.data.iter()));
+        } else {
+            panic!("first variable isn't Float");
+        }
+    }
+
+    #[test]
+    fn it_counts_elements() {
+        let f = open_sample1();
+        each_variable!(&f.variables[2], c => assert_eq!(c.data.len(), 25));
+
+        let text: NCData<char> = NCData::new(b"abcde\0\0\0".to_vec());
+        assert_eq!(text.len(), 8);
+        assert_eq!(text.iter().take(5).collect::<String>(), "abcde");
+
+        let shorts: NCData<i16> = NCData::new(vec![0; 5]);
+        assert_eq!(shorts.len(), 2);
+        assert!(!shorts.is_empty());
+        assert!(NCData::<f64>::new(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn it_surfaces_truncated_elements() {
+        let ints: NCData<i32> = NCData::new(vec![0, 0, 0, 1, 0, 0, 0, 2, 0, 0]);
+        let items: Vec<Result<i32>> = ints.try_iter().collect();
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].as_ref().ok(), Some(&1));
+        assert_eq!(items[1].as_ref().ok(), Some(&2));
+        assert!(matches!(items[2], Err(ParseError::UnexpectedEof)));
+
+        let whole: NCData<i32> = NCData::new(vec![0, 0, 0, 1]);
+        assert_eq!(whole.try_iter().map(|v| v.unwrap()).collect::<Vec<_>>(), [1]);
+    }
+
+    #[test]
+    fn it_iterates_backwards() {
+        let f = open_sample1();
+
+        if let NCVariable::Float(n) = &f.variables[0] {
+            let back: Vec<f32> = n.data.iter().rev().collect();
+            let mut front: Vec<f32> = n.data.iter().collect();
+            front.reverse();
+            assert_eq!(back, front);
+            assert_eq!(back[699], -24.95);
+        } else {
+            panic!("first variable isn't Float");
+        }
+
+        // a trailing partial element is never yielded
+        let shorts: NCData<i16> = NCData::new(vec![0, 1, 0, 2, 0, 3, 0]);
+        let mut iter = shorts.iter();
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back(), Some(2));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn it_gets_elements_by_index() {
+        let f = open_sample1();
+
+        if let NCVariable::Float(n) = &f.variables[0] {
+            assert_eq!(n.data.get(0), Some(-24.95));
+            assert_eq!(n.data.get(699), n.data.iter().next_back());
+            assert_eq!(n.data.get(700), None);
+            assert_eq!(n.data.get(usize::MAX), None);
+        } else {
+            panic!("first variable isn't Float");
+        }
+
+        let text: NCData<char> = NCData::new(b"abc".to_vec());
+        assert_eq!(text.get(2), Some('c'));
+
+        let shorts: NCData<i16> = NCData::new(vec![0, 1, 0, 2, 0, 3]);
+        let mut iter = shorts.iter();
+        assert_eq!(iter.nth(1), Some(2));
+        assert_eq!(iter.next(), Some(3));
+    }
+
+    #[test]
+    fn it_borrows_byte_data() {
+        let bytes: NCData<u8> = NCData::new(vec![1, 2, 3, 4]);
+        assert!(matches!(bytes.as_cow(), Cow::Borrowed(&[1, 2, 3, 4])));
+        let bytes: NCData<i8> = NCData::new(vec![1, 0xff, 0x80]);
+        assert!(matches!(bytes.as_cow(), Cow::Owned(v) if v == [1, -1, -128]));
+
+        let f = open_sample1();
+        if let NCVariable::Float(n) = &f.variables[0] {
+            let values = n.data.as_cow();
+            assert!(matches!(values, Cow::Owned(_)));
+            assert_eq!(values[..2], [-24.95, -24.85]);
+        } else {
+            panic!("first variable isn't Float");
+        }
+    }
+
+    #[test]
+    fn it_reads_into_fixed_size_arrays() {
+        let raw = [1.5f64, -2.0, 3.25].iter().flat_map(|v| v.to_be_bytes()).collect();
+        let v = NCVariableContainer::<f64> {
+            name: String::from("x"),
+            dimids: vec![0],
+            attributes: Vec::new(),
+            vsize: 24,
+            offset: 0,
+            data: NCData::new(raw),
+        };
+
+        assert_eq!(v.to_array::<3>(), Some([1.5, -2.0, 3.25]));
+        assert_eq!(v.to_array::<2>(), None);
+        assert_eq!(v.to_array::<4>(), None);
+    }
+
+    #[test]
+    fn it_transposes_to_column_major() {
+        let raw = (1..=6).flat_map(|v| f64::from(v).to_be_bytes()).collect();
+        let v = NCVariableContainer::<f64> {
+            name: String::from("m"),
+            dimids: vec![0, 1],
+            attributes: Vec::new(),
+            vsize: 48,
+            offset: 0,
+            data: NCData::new(raw),
+        };
+
+        assert_eq!(v.to_column_major(2, 3), Some(vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]));
+        assert_eq!(v.to_column_major(6, 1), Some(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+        assert_eq!(v.to_column_major(2, 2), None);
+        assert_eq!(v.to_column_major(usize::MAX, 2), None);
+    }
+
+    #[test]
+    fn it_finds_variables_by_name() {
+        let mut f = open_sample1();
+
+        assert!(matches!(f.variable("time"), Some(NCVariable::Int(_))));
+        assert!(matches!(f.variable("unknown"), Some(NCVariable::Short(_))));
+        assert!(f.variable("missing").is_none());
+
+        if let Some(NCVariable::Float(c)) = f.variable_mut("latitude") {
+            c.name = String::from("lat");
+        }
+        assert!(f.variable("latitude").is_none());
+        assert!(f.variable("lat").is_some());
+    }
+
+    #[test]
+    fn it_lists_record_variables() {
+        use crate::test_util::*;
+
+        assert!(open_sample1().record_variables().is_empty());
+
+        let f = file(
+            vec![dim("time", 0), dim("x", 2)],
+            vec![
+                float_var("x", &[1], &[]),
+                float_var("temp", &[0, 1], &[]),
+                float_var("transposed", &[1, 0], &[]),
+                float_var("time", &[0], &[]),
+            ],
+        );
+        let names: Vec<_> = f
+            .record_variables()
+            .iter()
+            .map(|v| v.name())
+            .collect();
+        assert_eq!(names, ["temp", "time"]);
+    }
+
+    #[test]
+    fn it_reports_variable_shapes() {
+        use crate::test_util::{dim, file, float_container};
+
+        let f = open_sample1();
+
+        match f.variable("unknown").unwrap() {
+            NCVariable::Short(c) => assert_eq!(c.shape(&f), [25, 400, 700]),
+            _ => panic!("unknown isn't Short"),
+        }
+
+        let mut f = file(vec![dim("time", 0), dim("x", 2)], Vec::new());
+        f.numrecs = 4;
+        let v = float_container("v", &[0, 1], &[]);
+        assert_eq!(v.shape(&f), [4, 2]);
+    }
+
+    #[test]
+    fn it_reports_types() {
+        let f = open_sample1();
+
+        let types: Vec<NCType> = f.variables.iter().map(NCVariable::nc_type).collect();
+        assert_eq!(types, [NCType::Float, NCType::Float, NCType::Int, NCType::Short]);
+        assert_eq!(f.attribute("Conventions").unwrap().nc_type(), NCType::Char);
+
+        assert_eq!(NCType::from_code(NC_SHORT), Some(NCType::Short));
+        assert_eq!(NCType::from_code(12), None);
+        assert_eq!(NCType::Double.code(), NC_DOUBLE);
+        assert_eq!(NCType::Double.size(), 8);
+        assert_eq!(NCType::Float.name(), "float");
+    }
+
+    #[test]
+    fn it_decodes_cdf5_types() {
+        use std::io::Cursor;
+
+        let mut bytes = Vec::new();
+        for word in &[1u32, 0x7500_0000, NC_USHORT as u32, 3] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        for v in &[1u16, 65535, 2] {
+            bytes.extend_from_slice(&v.to_be_bytes());
+        }
+        bytes.extend_from_slice(&[0, 0]);
+        let f = crate::test_util::file(Vec::new(), Vec::new());
+        match f.parse_attr(&mut Cursor::new(bytes)).unwrap() {
+            NCAttribute::UShort(c) => assert_eq!(c.values, [1, 65535, 2]),
+            a => panic!("{:?} isn't UShort", a),
+        }
+
+        let raw: Vec<u8> = [-2i64, 1 << 40].iter().flat_map(|v| v.to_be_bytes()).collect();
+        assert_eq!(NCDataIter::<i64>::new(&raw).collect::<Vec<_>>(), [-2, 1 << 40]);
+        assert_eq!(NCDataIter::<u64>::new(&raw).next_back(), Some(1 << 40));
+        assert_eq!(NCDataIter::<u32>::new(&raw).next(), Some(u32::MAX));
+        assert_eq!(NCDataIter::<i8>::new(&[0xff, 0x7f]).collect::<Vec<_>>(), [-1, 127]);
+        assert_eq!(NCDataIter::<u8>::new(&[0xff, 0x7f]).collect::<Vec<_>>(), [255, 127]);
+
+        assert_eq!(NCType::from_code(NC_UINT64), Some(NCType::UInt64));
+        assert_eq!(NCType::UShort.size(), 2);
+        assert_eq!(NCType::Int64.name(), "int64");
+        assert_eq!(u16::default_fill(), FILL_USHORT);
+
+        let mut huge = Cursor::new(vec![0; 16]);
+        assert!(read_values::<_, u64>(&mut huge, usize::MAX / 2, 8).is_err());
+    }
+
+    #[test]
+    fn it_exposes_typed_default_fills() {
+        // the values documented in netcdf.h
+        assert_eq!(DEFAULT_FILL_FLOAT, 9.96921e36f32);
+        assert_eq!(DEFAULT_FILL_DOUBLE, 9.969209968386869e36);
+        assert_eq!(f32::default_fill(), DEFAULT_FILL_FLOAT);
+        assert_eq!(DEFAULT_FILL_BYTE, -127);
+        assert_eq!(DEFAULT_FILL_SHORT, -32767);
+        assert_eq!(DEFAULT_FILL_INT, -2147483647);
+        assert_eq!(DEFAULT_FILL_INT64, -9223372036854775806);
+
+        match NCType::Double.default_fill() {
+            NCAttribute::Double(c) => {
+                assert_eq!(c.name, "_FillValue");
+                assert_eq!(c.values, [9.969209968386869e36]);
+            }
+            a => panic!("{:?} isn't Double", a),
+        }
+        assert_eq!(NCType::Short.default_fill().to_f64_vec(), Some(vec![-32767.0]));
+        assert_eq!(NCType::Char.default_fill().as_text().as_deref(), Some("\0"));
+    }
+
+    #[test]
+    fn it_finds_variable_attributes() {
+        let f = open_sample1();
+
+        match f.variable("unknown") {
+            Some(NCVariable::Short(c)) => {
+                assert_eq!(c.attribute("_FillValue").unwrap().to_f64_vec(), Some(vec![-32767.0]));
+                assert!(c.attribute("units").is_none());
+            }
+            _ => panic!("unknown isn't Short"),
+        }
+        each_variable!(&f.variables[0], c => {
+            assert_eq!(c.attribute("units").unwrap().as_text().as_deref(), Some("degrees_east"));
+        });
+    }
+
+    #[test]
+    fn it_reads_names() {
+        let f = open_sample1();
+
+        let names: Vec<&str> = f.variables.iter().map(NCVariable::name).collect();
+        assert_eq!(names, ["longitude", "latitude", "time", "unknown"]);
+        assert_eq!(f.variables[0].attributes()[0].name(), "units");
+    }
+
+    #[test]
+    fn it_finds_global_attributes() {
+        let f = open_sample1();
+
+        let conventions = f.attribute("Conventions").unwrap();
+        assert_eq!(conventions.name(), "Conventions");
+        assert_eq!(conventions.as_text().as_deref(), Some("CF-1.6"));
+        assert!(f.attribute("title").is_none());
+    }
+
+    #[test]
+    fn it_matches_variable_names() {
+        let f = open_sample1();
+        let names = |pattern| -> Vec<&str> {
+            f.variables_matching(pattern).iter().map(|v| v.name()).collect()
+        };
+
+        assert_eq!(names("*tude"), ["longitude", "latitude"]);
+        assert_eq!(names("t?me"), ["time"]);
+        assert_eq!(names("*"), ["longitude", "latitude", "time", "unknown"]);
+        assert!(names("lat").is_empty());
+        assert_eq!(names("*i*u*e"), ["longitude", "latitude"]);
+        assert_eq!(names("?*"), ["longitude", "latitude", "time", "unknown"]);
+        assert!(names("time?").is_empty());
+
+        // exponential for a matcher that tries every split at every `*`
+        let long: Vec<char> = "a".repeat(200).chars().collect();
+        let pattern: Vec<char> = "*a*a*a*a*a*a*a*a*b".chars().collect();
+        assert!(!glob_match(&pattern, &long));
+    }
+
+    #[test]
+    fn it_finds_dimensions() {
+        let f = open_sample1();
+
+        assert_eq!(f.dimension("latitude").map(|d| d.length), Some(400));
+        assert!(f.dimension("depth").is_none());
+
+        let names: Vec<_> = match f.variable("unknown") {
+            Some(NCVariable::Short(c)) => {
+                c.dimids.iter().map(|&id| &f.dimension_by_id(id).unwrap().name).collect()
+            }
+            _ => panic!("unknown isn't Short"),
+        };
+        assert_eq!(names, ["time", "latitude", "longitude"]);
+        assert!(f.dimension_by_id(3).is_none());
+    }
+
+    #[test]
+    fn it_gives_up_past_the_deadline() {
+        use std::io::{Cursor, Read, Seek, SeekFrom};
+        use std::thread;
+        use std::time::Duration;
+
+        /// Sleeps before every read, counting them.
+        struct SlowReader(Cursor<Vec<u8>>, usize);
+
+        impl Read for SlowReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                thread::sleep(Duration::from_millis(5));
+                self.1 += 1;
+                self.0.read(buf)
+            }
+        }
+
+        impl Seek for SlowReader {
+            fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+                self.0.seek(pos)
+            }
+        }
+
+        // the header alone takes dozens of reads, so the deadline passes
+        // partway through
+        let mut slow = SlowReader(Cursor::new(fs::read(SAMPLE_FILE_1).unwrap()), 0);
+        let options = ReaderOptions {
+            deadline: Some(Instant::now() + Duration::from_millis(20)),
+            ..ReaderOptions::default()
+        };
+        let err = NCFile::with_options(&mut slow, &options).unwrap_err();
+        assert_eq!(err.to_string(), "read deadline exceeded");
+        assert!(slow.1 > 0);
+
+        let mut r = Cursor::new(fs::read(SAMPLE_FILE_1).unwrap());
+        let options = ReaderOptions {
+            deadline: Instant::now().checked_sub(Duration::from_secs(1)),
+            ..ReaderOptions::default()
+        };
+        assert!(options.deadline.is_some());
+        let err = NCFile::with_options(&mut r, &options).unwrap_err();
+        assert_eq!(err.to_string(), "read deadline exceeded");
+
+        r.set_position(0);
+        let options = ReaderOptions {
+            deadline: Some(Instant::now() + Duration::from_secs(60)),
+            ..ReaderOptions::default()
+        };
+        assert_eq!(NCFile::with_options(&mut r, &options).unwrap().variables.len(), 4);
+    }
+
+    #[test]
+    fn it_reads_truncated_files() {
+        use std::io::Cursor;
+
+        let mut bytes = fs::read(SAMPLE_FILE_1).unwrap();
+        let (_, offset) = open_sample1().variable("unknown").unwrap().layout();
+        bytes.truncate(offset as usize + 100);
+
+        assert!(NCFile::new(&mut Cursor::new(&bytes)).is_err());
+
+        let options = ReaderOptions {
+            allow_truncated: true,
+            ..ReaderOptions::default()
+        };
+        let f = NCFile::with_options(&mut Cursor::new(&bytes), &options).unwrap();
+
+        assert!(!f.variables[0].is_partial());
+        assert!(!f.variables[2].is_partial());
+        assert_eq!(f.variables[0].to_f64_vec().unwrap()[0], f64::from(-24.95f32));
+
+        let unknown = f.variable("unknown").unwrap();
+        assert!(unknown.is_partial());
+        each_variable!(unknown, c => assert_eq!(c.data.raw().len(), 100));
+    }
+
+    #[test]
+    fn it_loads_data_lazily() {
+        use std::io::Cursor;
+
+        let f = NCFile::open_lazy(SAMPLE_FILE_1).unwrap();
+        let eager = open_sample1();
+        assert_eq!(f.dimensions.len(), 3);
+        assert!(f.variables.iter().all(|v| each_variable!(v, c => !c.data.is_loaded())));
+
+        match (&f.variables[3], &eager.variables[3]) {
+            (NCVariable::Short(lazy), NCVariable::Short(c)) => {
+                assert_eq!(lazy.data.get(12345), c.data.get(12345));
+                assert!(lazy.data.is_loaded());
+                assert!(!lazy.data.is_partial());
+            }
+            _ => panic!("unknown isn't Short"),
+        }
+        assert!(each_variable!(&f.variables[0], c => !c.data.is_loaded()));
+
+        let mut bytes = fs::read(SAMPLE_FILE_1).unwrap();
+        bytes.truncate(bytes.len() - 100);
+        let f = NCFile::new_lazy(Cursor::new(bytes)).unwrap();
+        each_variable!(&f.variables[0], c => assert!(c.data.load().is_ok()));
+        each_variable!(&f.variables[3], c => assert!(c.data.load().is_err()));
+
+        // a failed read isn't mistaken for empty data later on
+        match &f.variables[3] {
+            NCVariable::Short(c) => {
+                assert_eq!(c.data.iter().count(), 0);
+                assert!(!c.data.is_loaded());
+                assert!(c.data.load().is_err());
+                assert!(c.data.try_iter().next().unwrap().is_err());
+                assert!(c.data.try_iter().next().unwrap().is_err());
+            }
+            _ => panic!("unknown isn't Short"),
+        }
+    }
+
+    #[test]
+    fn it_counts_streamed_records() {
+        use std::io::Cursor;
+
+        let mut bytes = b"CDF\x01".to_vec();
+        for word in &[STREAMING, 0x0a, 1, 4] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        bytes.extend_from_slice(b"time");
+        for word in &[0u32, 0, 0, 0x0b, 1, 1] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        bytes.extend_from_slice(b"t\0\0\0");
+        for word in &[1u32, 0, 0, 0, 5, 4, 80] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        assert_eq!(bytes.len(), 80);
+        for v in &[1.5f32, 2.5, 3.5] {
+            bytes.extend_from_slice(&v.to_be_bytes());
+        }
+
+        let f = NCFile::new(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(f.numrecs, 3);
+        assert!(f.is_record_variable(&f.variables[0]));
+        assert_eq!(f.resolve_shape(f.variables[0].dimids()), [3]);
+    }
+
+    #[test]
+    fn it_decodes_text_with_declared_encoding() {
+        use crate::test_util::char_attr;
+
+        let mut c = NCVariableContainer::<char> {
+            name: "city".to_string(),
+            dimids: vec![0],
+            attributes: Vec::new(),
+            vsize: 8,
+            offset: 0,
+            data: NCData::new(b"Z\xfcrich\xe9".to_vec()),
+        };
+        assert_eq!(c.text(), "Z\u{fffd}rich\u{fffd}");
+
+        c.attributes.push(char_attr("_Encoding", "ISO-8859-1"));
+        assert_eq!(c.text(), "Züriché");
+
+        c.attributes[0] = char_attr("_Encoding", "utf-8");
+        c.data = NCData::new("Zürich".as_bytes().to_vec());
+        assert_eq!(c.text(), "Zürich");
+    }
+
+    #[test]
+    fn it_rejects_oversized_header_lengths() {
+        use std::io::Cursor;
+
+        // a CDF-5 header declaring one dimension with a name of `len` bytes
+        let dimension = |len: u64| {
+            let mut bytes = b"CDF\x05".to_vec();
+            bytes.extend_from_slice(&0u64.to_be_bytes());
+            bytes.extend_from_slice(&u32::from(NC_DIMENSION).to_be_bytes());
+            bytes.extend_from_slice(&1u64.to_be_bytes());
+            bytes.extend_from_slice(&len.to_be_bytes());
+            bytes
+        };
+        assert!(NCFile::new(&mut Cursor::new(dimension(u64::MAX))).is_err());
+        let err = NCFile::new(&mut Cursor::new(dimension(u64::MAX - 8))).unwrap_err();
+        assert!(matches!(err.kind(), ParseError::UnexpectedEof));
+        // located at the start of the name, i.e. its length
+        assert_eq!(err.offset(), Some(24));
+
+        // a variable over a huge dimension, claiming far more data than the
+        // stream holds
+        let mut bytes = b"CDF\x05".to_vec();
+        bytes.extend_from_slice(&0u64.to_be_bytes());
+        bytes.extend_from_slice(&u32::from(NC_DIMENSION).to_be_bytes());
+        bytes.extend_from_slice(&1u64.to_be_bytes());
+        bytes.extend_from_slice(&1u64.to_be_bytes());
+        bytes.extend_from_slice(b"x\0\0\0");
+        bytes.extend_from_slice(&(1u64 << 60).to_be_bytes());
+        bytes.extend_from_slice(&[0; 12]);
+        bytes.extend_from_slice(&u32::from(NC_VARIABLE).to_be_bytes());
+        bytes.extend_from_slice(&1u64.to_be_bytes());
+        bytes.extend_from_slice(&1u64.to_be_bytes());
+        bytes.extend_from_slice(b"v\0\0\0");
+        bytes.extend_from_slice(&1u64.to_be_bytes());
+        bytes.extend_from_slice(&0u64.to_be_bytes());
+        bytes.extend_from_slice(&[0; 12]);
+        bytes.extend_from_slice(&u32::from(NC_BYTE).to_be_bytes());
+        bytes.extend_from_slice(&(1u64 << 60).to_be_bytes());
+        bytes.extend_from_slice(&0u64.to_be_bytes());
+        let err = NCFile::new(&mut Cursor::new(bytes)).unwrap_err();
+        assert!(matches!(err.kind(), ParseError::UnexpectedEof));
+    }
+
+    #[test]
+    fn it_rejects_empty_input() {
+        use std::io::Cursor;
+
+        assert!(NCFile::new(&mut Cursor::new(Vec::new())).is_err());
+        assert!(NCFile::new(&mut Cursor::new(b"CD".to_vec())).is_err());
+    }
+
+    #[test]
+    fn it_reports_error_kinds() {
+        use std::io::Cursor;
+
+        let err = NCFile::new(&mut Cursor::new(Vec::new())).unwrap_err();
+        assert!(matches!(err, ParseError::UnexpectedEof));
+        let err = NCFile::new(&mut Cursor::new(b"HDF\x01".to_vec())).unwrap_err();
+        assert!(matches!(err, ParseError::BadMagic));
+        assert_eq!(err.to_string(), "incorrect magic number");
+        let err = NCFile::new(&mut Cursor::new(b"\x89HDF\r\n\x1a\n\0\0".to_vec())).unwrap_err();
+        assert!(matches!(err, ParseError::Netcdf4Unsupported));
+        assert_eq!(err.to_string(), "netCDF-4 (HDF5) files are not supported");
+        let err = NCFile::new(&mut Cursor::new(b"\x89HDF\r\n".to_vec())).unwrap_err();
+        assert!(matches!(err, ParseError::BadMagic));
+
+        let mut bytes = fs::read(SAMPLE_FILE_1).unwrap();
+        let pos = corrupt_second_variable_type(&mut bytes);
+        let err = NCFile::new(&mut Cursor::new(bytes)).unwrap_err();
+        assert!(matches!(err.kind(), ParseError::UnknownType(0x0c)));
+        assert_eq!(err.offset(), Some(pos as u64));
+        assert_eq!(err.to_string(), format!("unknown type 0x0c at offset {}", pos));
+
+        let err = NCFile::open("./samples/missing.nc").unwrap_err();
+        assert!(matches!(err, ParseError::Io(_)));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn it_parses_cdf5() {
+        let f = NCFile::open(SAMPLE_FILE_3).unwrap();
+
+        assert_eq!(f.version, 5);
+        assert_eq!(f.numrecs, 0);
+        assert_eq!(f.dimension("big").unwrap().length, 5_000_000_000);
+        assert_eq!(f.dimension("x").unwrap().length, 3);
+        assert_eq!(f.attribute("title").unwrap().as_text().unwrap(), "CDF-5 sample");
+        assert_eq!(f.header_len(), 220);
+
+        match f.variable("x").unwrap() {
+            NCVariable::Double(x) => {
+                assert_eq!((x.vsize, x.offset), (24, 220));
+                assert_eq!(x.attribute("units").unwrap().as_text().unwrap(), "m");
+                assert_eq!(x.data.iter().collect::<Vec<_>>(), [0.5, 1.5, 2.5]);
+            }
+            _ => panic!("x isn't Double"),
+        }
+    }
+
+    #[test]
+    fn it_reads_interleaved_records() {
+        use std::io::Cursor;
+
+        let mut bytes = b"CDF\x01".to_vec();
+        for word in &[2u32, 0x0a, 2, 4] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        bytes.extend_from_slice(b"time");
+        for word in &[0u32, 1] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        bytes.extend_from_slice(b"x\0\0\0");
+        for word in &[3u32, 0, 0, 0x0b, 2, 1] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        bytes.extend_from_slice(b"a\0\0\0");
+        for word in &[1u32, 0, 0, 0, 5, 4, 132, 1] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        bytes.extend_from_slice(b"b\0\0\0");
+        for word in &[2u32, 0, 1, 0, 0, 3, 8, 136] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        assert_eq!(bytes.len(), 132);
+        for (a, b) in &[(1.5f32, [1i16, 2, 3]), (2.5, [4, 5, 6])] {
+            bytes.extend_from_slice(&a.to_be_bytes());
+            for v in b {
+                bytes.extend_from_slice(&v.to_be_bytes());
+            }
+            bytes.extend_from_slice(&[0, 0]);
+        }
+
+        let check = |f: &NCFile| {
+            assert_eq!(f.record_size(), 12);
+            match &f.variables[0] {
+                NCVariable::Float(a) => assert_eq!(a.data.iter().collect::<Vec<_>>(), [1.5, 2.5]),
+                _ => panic!("a isn't Float"),
+            }
+            match &f.variables[1] {
+                NCVariable::Short(b) => {
+                    assert!(!b.data.is_partial());
+                    assert_eq!(b.data.iter().collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6]);
+                }
+                _ => panic!("b isn't Short"),
+            }
+        };
+
+        check(&NCFile::new(&mut Cursor::new(bytes.clone())).unwrap());
+        check(&NCFile::new_lazy(Cursor::new(bytes.clone())).unwrap());
+
+        let options = ReaderOptions {
+            max_elements: Some(4),
+            ..ReaderOptions::default()
+        };
+        let mut f = NCFile::with_options(&mut Cursor::new(bytes.clone()), &options).unwrap();
+        if let NCVariable::Short(b) = &f.variables[1] {
+            assert!(b.data.is_partial());
+            assert_eq!(b.data.iter().collect::<Vec<_>>(), [1, 2, 3, 4]);
+        }
+
+        // record data is read under the deadline too
+        let options = ReaderOptions {
+            deadline: Instant::now().checked_sub(std::time::Duration::from_secs(1)),
+            ..ReaderOptions::default()
+        };
+        let err = f.read_records(&mut Cursor::new(bytes), &options).unwrap_err();
+        assert_eq!(err.kind().to_string(), "read deadline exceeded");
+    }
+
+    #[test]
+    fn it_opens_sampled() {
+        let f = NCFile::open_sampled(SAMPLE_FILE_1, 3).unwrap();
+
+        for v in &f.variables {
+            each_variable!(v, c => {
+                assert!(c.data.is_partial());
+                assert_eq!(c.data.iter().count(), 3);
+            });
+        }
+
+        if let NCVariable::Float(n) = &f.variables[0] {
+            assert_eq!(n.data.iter().next_back().unwrap(), -24.75);
+        }
+
+        let f = NCFile::open_sampled(SAMPLE_FILE_1, 25).unwrap();
+        if let NCVariable::Int(t) = &f.variables[2] {
+            assert!(!t.data.is_partial());
+        }
+    }
+
+    #[test]
+    fn it_ignores_padding_when_sampling() {
+        use std::io::Cursor;
+
+        // one dimension x = 3 and a short variable s(x), whose 6 bytes of
+        // data are padded to a vsize of 8
+        let mut bytes = b"CDF\x01".to_vec();
+        for word in &[0, 0x0a, 1, 1, u32::from_be_bytes(*b"x\0\0\0"), 3, 0, 0] {
+            bytes.extend_from_slice(&u32::to_be_bytes(*word));
+        }
+        for word in &[0x0b, 1, 1, u32::from_be_bytes(*b"s\0\0\0"), 1, 0, 0, 0, 3, 8] {
+            bytes.extend_from_slice(&u32::to_be_bytes(*word));
+        }
+        let begin = bytes.len() as u32 + 4;
+        bytes.extend_from_slice(&begin.to_be_bytes());
+        bytes.extend_from_slice(&[0, 1, 0, 2, 0, 3, 0, 0]);
+
+        for (max, partial) in [(2, true), (3, false), (4, false)] {
+            let options = ReaderOptions {
+                max_elements: Some(max),
+                ..ReaderOptions::default()
+            };
+            let f = NCFile::with_options(&mut Cursor::new(bytes.clone()), &options).unwrap();
+            assert_eq!(f.variables[0].is_partial(), partial, "max_elements {}", max);
+        }
+
+        // the padding isn't read as a fourth element, eagerly or lazily
+        let f = NCFile::new(&mut Cursor::new(bytes.clone())).unwrap();
+        if let NCVariable::Short(s) = &f.variables[0] {
+            assert_eq!(s.data.len(), 3);
+            assert_eq!(s.quantile(0.5), Some(2.0));
+        }
+        let f = NCFile::new_lazy(Cursor::new(bytes)).unwrap();
+        if let NCVariable::Short(s) = &f.variables[0] {
+            assert_eq!(s.data.iter().collect::<Vec<_>>(), [1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn it_reads_latin1_char_attributes() {
+        use std::io::Cursor;
+
+        // no dimensions or variables, and a global attribute title = "café"
+        // stored in Latin-1
+        let mut bytes = b"CDF\x01".to_vec();
+        for word in &[0, 0, 0, 0x0c, 1, 5] {
+            bytes.extend_from_slice(&u32::to_be_bytes(*word));
+        }
+        bytes.extend_from_slice(b"title\0\0\0");
+        for word in &[2, 4] {
+            bytes.extend_from_slice(&u32::to_be_bytes(*word));
+        }
+        bytes.extend_from_slice(b"caf\xe9");
+        bytes.extend_from_slice(&[0; 8]);
+
+        let f = NCFile::with_options(&mut Cursor::new(bytes), &ReaderOptions::default()).unwrap();
+        assert_eq!(f.attributes[0].as_text().as_deref(), Some("café"));
+    }
 }
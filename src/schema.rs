@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use crate::reader::*;
+
+/// Variable attributes that must agree for records to be concatenated.
+const CONCAT_ATTRIBUTES: [&str; 4] = ["units", "_FillValue", "scale_factor", "add_offset"];
+
+impl NCFile {
+    /// Checks whether the records of `other` could be appended to this file:
+    /// fixed dimensions must match, and both files must declare the same
+    /// variables with identical types, dimensions and packing attributes.
+    /// Returns an error describing the first mismatch found.
+    pub fn concat_compatible(&self, other: &NCFile) -> Result<()> {
+        let fixed = |f: &NCFile| -> Vec<(String, u64)> {
+            f.dimensions
+                .iter()
+                .filter(|d| d.length != 0)
+                .map(|d| (d.name.clone(), d.length))
+                .collect()
+        };
+        let (ours, theirs) = (fixed(self), fixed(other));
+
+        for (name, length) in &ours {
+            match theirs.iter().find(|(n, _)| n == name) {
+                Some((_, l)) if l != length => {
+                    return Err(ParseError::new(&format!(
+                        "dimension {} has length {} in one file and {} in the other", name, length, l
+                    )));
+                }
+                Some(_) => {}
+                None => return Err(ParseError::new(&format!("dimension {} is missing", name))),
+            }
+        }
+        if ours.len() != theirs.len() {
+            return Err(ParseError::new("files have different numbers of fixed dimensions"));
+        }
+
+        if self.variables.len() != other.variables.len() {
+            return Err(ParseError::new("files have different numbers of variables"));
+        }
+
+        for a in &self.variables {
+            let name = a.name();
+            let b = other
+                .variable(name)
+                .ok_or_else(|| ParseError::new(&format!("variable {} is missing", name)))?;
+
+            if a.nc_type() != b.nc_type() {
+                return Err(ParseError::new(&format!(
+                    "variable {} is {} in one file and {} in the other",
+                    name, a.nc_type().name(), b.nc_type().name()
+                )));
+            }
+
+            if self.dimension_names(a) != other.dimension_names(b)
+                || self.is_record_variable(a) != other.is_record_variable(b)
+            {
+                return Err(ParseError::new(&format!("variable {} has different dimensions", name)));
+            }
+
+            for attr in CONCAT_ATTRIBUTES.iter() {
+                let x = a.attributes().iter().find(|x| x.has_name(attr));
+                let y = b.attributes().iter().find(|y| y.has_name(attr));
+                let same = match (x, y) {
+                    (Some(x), Some(y)) => attribute_values_eq(x, y),
+                    (None, None) => true,
+                    _ => false,
+                };
+
+                if !same {
+                    return Err(ParseError::new(&format!(
+                        "attribute {} of variable {} differs", attr, name
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fingerprints the logical content of the file: its dimensions,
+    /// attributes, variable definitions and variable data. Storage details
+    /// such as the format version, data offsets and trailing padding don't
+    /// contribute, so files that only differ in layout hash equally.
+    ///
+    /// The hash is 64-bit FNV-1a over a fixed encoding of the content, so it
+    /// is the same on every platform and across Rust and crate releases and
+    /// can be stored to check files against later.
+    pub fn content_hash(&self) -> u64 {
+        let mut h = Fnv1a::new();
+
+        h.write_u64(self.numrecs);
+        h.write_u64(self.dimensions.len() as u64);
+        for d in &self.dimensions {
+            h.write_str(&d.name);
+            h.write_u64(d.length);
+        }
+
+        hash_attributes(&self.attributes, &mut h);
+
+        h.write_u64(self.variables.len() as u64);
+        for v in &self.variables {
+            h.write(&[v.nc_type().code()]);
+            h.write_u64(v.dimids().len() as u64);
+            for &id in v.dimids() {
+                h.write_u64(u64::from(id));
+            }
+            hash_attributes(v.attributes(), &mut h);
+
+            let size = v.nc_type().size();
+            let len = self.resolve_shape(v.dimids()).iter().product::<usize>() * size;
+            each_variable!(v, c => {
+                h.write_str(&c.name);
+                let data = &c.data.raw()[..len.min(c.data.raw().len())];
+                h.write_u64(data.len() as u64);
+                h.write(data);
+            });
+        }
+
+        h.0
+    }
+
+    /// Buckets variables by the set of dimensions they span, keyed by the
+    /// sorted dimension ids. Variables sharing a key share a grid.
+    pub fn group_by_dims(&self) -> HashMap<Vec<u32>, Vec<&NCVariable>> {
+        let mut groups: HashMap<Vec<u32>, Vec<&NCVariable>> = HashMap::new();
+
+        for v in &self.variables {
+            let mut key = v.dimids().to_vec();
+            key.sort_unstable();
+            groups.entry(key).or_default().push(v);
+        }
+
+        groups
+    }
+
+    pub(crate) fn dimension_names(&self, var: &NCVariable) -> Vec<&str> {
+        var.dimids()
+            .iter()
+            .map(|&id| self.dimension_by_id(id).map_or("", |d| d.name.as_str()))
+            .collect()
+    }
+}
+
+/// The 64-bit FNV-1a hash, whose output is fixed by its specification.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u64(&mut self, v: u64) {
+        self.write(&v.to_le_bytes());
+    }
+
+    /// Writes the length before the bytes so neighbouring strings can't
+    /// run into each other.
+    fn write_str(&mut self, s: &str) {
+        self.write_u64(s.len() as u64);
+        self.write(s.as_bytes());
+    }
+}
+
+fn hash_attributes(attributes: &[NCAttribute], h: &mut Fnv1a) {
+    h.write_u64(attributes.len() as u64);
+    for a in attributes {
+        h.write_str(a.name());
+        h.write(&[a.nc_type().code()]);
+        match a.as_text() {
+            Some(text) => h.write_str(&text),
+            None => {
+                let values = a.to_f64_vec().unwrap_or_default();
+                h.write_u64(values.len() as u64);
+                for v in values {
+                    h.write_u64(v.to_bits());
+                }
+            }
+        }
+    }
+}
+
+fn attribute_values_eq(a: &NCAttribute, b: &NCAttribute) -> bool {
+    a.nc_type() == b.nc_type()
+        && a.as_text() == b.as_text()
+        && a.to_f64_vec() == b.to_f64_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Fnv1a;
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    fn sample(lon: u64) -> NCFile {
+        let mut temp = float_container("temp", &[0, 1], &[]);
+        temp.attributes.push(float_attr("scale_factor", &[0.5]));
+
+        file(
+            vec![dim("time", 0), dim("lon", lon)],
+            vec![float_var("lon", &[1], &[]), NCVariable::Float(temp)],
+        )
+    }
+
+    #[test]
+    fn it_checks_concat_compatibility() {
+        let f = sample(3);
+        let mut g = sample(3);
+        g.numrecs = 10;
+        assert!(f.concat_compatible(&g).is_ok());
+
+        let err = f.concat_compatible(&sample(4)).unwrap_err();
+        assert_eq!(err.to_string(), "dimension lon has length 3 in one file and 4 in the other");
+
+        if let NCVariable::Float(v) = &mut g.variables[1] {
+            v.attributes[0] = float_attr("scale_factor", &[0.25]);
+        }
+        let err = f.concat_compatible(&g).unwrap_err();
+        assert_eq!(err.to_string(), "attribute scale_factor of variable temp differs");
+    }
+
+    #[test]
+    fn it_hashes_file_content() {
+        let f = NCFile::open("./samples/sample1.nc").unwrap();
+        let mut g = f.clone();
+        assert_eq!(f.content_hash(), g.content_hash());
+
+        // layout and padding don't count
+        if let NCVariable::Float(v) = &mut g.variables[0] {
+            v.offset += 4;
+            v.data.raw_mut().extend_from_slice(&[0; 4]);
+        }
+        assert_eq!(f.content_hash(), g.content_hash());
+
+        if let NCVariable::Float(v) = &mut g.variables[0] {
+            v.data.raw_mut()[0] ^= 0xff;
+        }
+        assert_ne!(f.content_hash(), g.content_hash());
+
+        // the value is pinned, so stored fingerprints stay valid
+        assert_eq!(sample(3).content_hash(), 0x3383_212d_3492_2a38);
+    }
+
+    #[test]
+    fn it_hashes_with_fnv1a() {
+        let mut h = Fnv1a::new();
+        h.write(b"a");
+        assert_eq!(h.0, 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn it_groups_variables_by_dimensions() {
+        let mut f = sample(3);
+        f.variables.push(float_var("pres", &[1, 0], &[]));
+
+        let groups = f.group_by_dims();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[&vec![1]].len(), 1);
+
+        let names: Vec<_> = groups[&vec![0, 1]]
+            .iter()
+            .map(|v| v.name())
+            .collect();
+        assert_eq!(names, ["temp", "pres"]);
+    }
+}
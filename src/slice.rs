@@ -0,0 +1,170 @@
+use crate::reader::*;
+
+impl<T> NCVariableContainer<T>
+where
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+{
+    /// Reads the hyperslab of `count` elements along each axis, beginning at
+    /// `start` and stepping by `stride`, like `nc_get_vars` in the netCDF C
+    /// library. Only the selected elements are read and decoded, so a
+    /// variable opened lazily stays unloaded; they're returned in row-major
+    /// order. The variable's shape is taken from its dimensions in `file`.
+    ///
+    /// Fails if `start`, `count` and `stride` don't each have one entry per
+    /// dimension, a stride is zero, the selection runs past the end of an
+    /// axis, or the variable holds less data than the selection needs.
+    pub fn read_slice(
+        &self,
+        file: &NCFile,
+        start: &[usize],
+        count: &[usize],
+        stride: &[usize],
+    ) -> Result<Vec<T>> {
+        let shape = self.shape(file);
+        let rank = shape.len();
+        if start.len() != rank || count.len() != rank || stride.len() != rank {
+            return Err(ParseError::new(&format!(
+                "slice of variable {} needs {} entries per argument", self.name, rank
+            )));
+        }
+
+        for axis in 0..rank {
+            if stride[axis] == 0 {
+                return Err(ParseError::new(&format!("stride along axis {} is zero", axis)));
+            }
+            let end = match count[axis] {
+                0 => Some(start[axis]),
+                n => (n - 1)
+                    .checked_mul(stride[axis])
+                    .and_then(|span| span.checked_add(start[axis]))
+                    .and_then(|last| last.checked_add(1)),
+            };
+            match end {
+                Some(end) if end <= shape[axis] => {}
+                _ => {
+                    return Err(ParseError::new(&format!(
+                        "slice along axis {} runs past length {}", axis, shape[axis]
+                    )))
+                }
+            }
+        }
+
+        let total = count
+            .iter()
+            .try_fold(1usize, |total, &n| total.checked_mul(n))
+            .ok_or_else(|| ParseError::new("slice selects too many elements"))?;
+        let mut values = Vec::with_capacity(total);
+        if total == 0 {
+            return Ok(values);
+        }
+
+        // read a row along the last axis at a time, in one piece if it's
+        // contiguous and element by element otherwise
+        let size = NCDataIter::<T>::element_size();
+        let (row, step) = match rank {
+            0 => (1, 1),
+            _ => (count[rank - 1], stride[rank - 1]),
+        };
+        let mut index = vec![0; rank];
+        for _ in 0..total / row {
+            let flat = (0..rank).fold(0, |acc, k| acc * shape[k] + start[k] + index[k] * stride[k]);
+            if step == 1 {
+                values.extend(NCDataIter::new(&self.read_elements(flat, row, size)?));
+            } else {
+                for i in 0..row {
+                    values.extend(NCDataIter::new(&self.read_elements(flat + i * step, 1, size)?));
+                }
+            }
+
+            // advance the outer axes, the fastest varying first
+            for k in (0..rank.saturating_sub(1)).rev() {
+                index[k] += 1;
+                if index[k] < count[k] {
+                    break;
+                }
+                index[k] = 0;
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// The stored bytes of `n` elements of `size` bytes beginning at element
+    /// `first`.
+    fn read_elements(&self, first: usize, n: usize, size: usize) -> Result<Vec<u8>> {
+        let last = first + n - 1;
+        let missing = || ParseError::new(&format!("variable {} has no element {}", self.name, last));
+        let start = first.checked_mul(size).ok_or_else(missing)?;
+        self.data.read_range(start, n * size).map_err(|_| missing())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    #[test]
+    fn it_reads_hyperslabs() {
+        let values: Vec<f32> = (0..12).map(|v| v as f32).collect();
+        let f = file(vec![dim("y", 3), dim("x", 4)], Vec::new());
+        let v = float_container("grid", &[0, 1], &values);
+
+        assert_eq!(v.read_slice(&f, &[1, 1], &[2, 2], &[1, 1]).unwrap(), [5.0, 6.0, 9.0, 10.0]);
+        assert_eq!(v.read_slice(&f, &[0, 0], &[2, 2], &[2, 3]).unwrap(), [0.0, 3.0, 8.0, 11.0]);
+        assert_eq!(v.read_slice(&f, &[0, 3], &[3, 1], &[1, 1]).unwrap(), [3.0, 7.0, 11.0]);
+        assert!(v.read_slice(&f, &[0, 0], &[0, 4], &[1, 1]).unwrap().is_empty());
+
+        assert!(v.read_slice(&f, &[0], &[1], &[1]).is_err());
+        assert!(v.read_slice(&f, &[0, 0], &[1, 1], &[1, 0]).is_err());
+        assert!(v.read_slice(&f, &[0, 2], &[1, 2], &[1, 2]).is_err());
+        assert!(v.read_slice(&f, &[3, 0], &[1, 1], &[1, 1]).is_err());
+    }
+
+    #[test]
+    fn it_slices_sample_variables() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+
+        match f.variable("unknown").unwrap() {
+            NCVariable::Short(c) => {
+                let slice = c.read_slice(&f, &[2, 10, 20], &[2, 1, 3], &[5, 1, 10]).unwrap();
+                let at = |t: usize, y: usize, x: usize| c.data.get((t * 400 + y) * 700 + x).unwrap();
+                let expected: Vec<i16> = [2, 7]
+                    .iter()
+                    .flat_map(|&t| [20, 30, 40].map(|x| at(t, 10, x)))
+                    .collect();
+                assert_eq!(slice, expected);
+            }
+            _ => panic!("unknown isn't Short"),
+        }
+    }
+
+    #[test]
+    fn it_slices_without_loading() {
+        let eager = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let lazy = NCFile::open_lazy(SAMPLE_FILE_1).unwrap();
+
+        match (eager.variable("unknown").unwrap(), lazy.variable("unknown").unwrap()) {
+            (NCVariable::Short(e), NCVariable::Short(l)) => {
+                let (start, count, stride) = ([3, 5, 100], [2, 3, 4], [10, 2, 1]);
+                let slice = l.read_slice(&lazy, &start, &count, &stride).unwrap();
+                assert_eq!(slice, e.read_slice(&eager, &start, &count, &stride).unwrap());
+                assert_eq!(slice.len(), 2 * 3 * 4);
+                assert!(!l.data.is_loaded());
+            }
+            _ => panic!("unknown isn't Short"),
+        }
+    }
+
+    #[test]
+    fn it_rejects_overflowing_selections() {
+        let f = file(vec![dim("x", 4)], Vec::new());
+        let v = float_container("x", &[0], &[0.0; 4]);
+
+        assert!(v.read_slice(&f, &[usize::MAX], &[2], &[1]).is_err());
+        assert!(v.read_slice(&f, &[1], &[2], &[usize::MAX]).is_err());
+        assert!(v.read_slice(&f, &[0], &[usize::MAX], &[usize::MAX]).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+use crate::fill::is_fill;
+use crate::reader::*;
+
+impl<T: NCNumeric> NCVariableContainer<T>
+where
+    for<'a> NCDataIter<'a, T>: Iterator<Item = T>,
+{
+    /// The valid values of the variable widened to `f64`, skipping fill and
+    /// missing values as well as NaNs.
+    pub(crate) fn valid_values(&self) -> Vec<f64> {
+        let fills = self.fill_values();
+
+        self.widened()
+            .filter(|&v| !v.is_nan() && !is_fill(&fills, v))
+            .collect()
+    }
+
+    /// Computes the `q`-th quantile of the valid values, linearly
+    /// interpolating between the two closest ranks. Returns `None` if `q`
+    /// is outside `0.0..=1.0` or the variable holds no valid values.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+
+        let mut values = self.valid_values();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+
+        let pos = q * (values.len() - 1) as f64;
+        let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+        Some(values[lo] + (values[hi] - values[lo]) * (pos - lo as f64))
+    }
+
+    /// Linearly interpolates the 1-D data at the fractional `index`, widening
+    /// to `f64`. Returns `None` if `index` falls outside the data or either
+    /// neighbouring element is a fill or missing value.
+    pub fn interp_at(&self, index: f64) -> Option<f64> {
+        let values = self.to_f64_with_nan_fill();
+        if !(0.0..=(values.len() as f64 - 1.0)).contains(&index) {
+            return None;
+        }
+
+        let (lo, hi) = (index.floor() as usize, index.ceil() as usize);
+        let (a, b) = (values[lo], values[hi]);
+        if a.is_nan() || b.is_nan() {
+            return None;
+        }
+
+        Some(a + (b - a) * (index - lo as f64))
+    }
+
+    /// Shrinks a 2-D variable of the given `[rows, cols]` shape by averaging
+    /// `factor` × `factor` blocks, returning the coarser shape and values.
+    /// Blocks along the bottom and right edges may be partial. Fill and
+    /// missing values are left out of each block's mean; blocks holding
+    /// only fill average to NaN. Returns `None` if `shape` isn't
+    /// two-dimensional, `factor` is zero, or the data holds fewer elements
+    /// than `shape`.
+    pub fn downsample(&self, shape: &[usize], factor: usize) -> Option<(Vec<usize>, Vec<f64>)> {
+        let &[rows, cols] = shape else {
+            return None;
+        };
+        let values = self.to_f64_with_nan_fill();
+        if factor == 0 || values.len() < rows.checked_mul(cols)? {
+            return None;
+        }
+
+        let (out_rows, out_cols) = (rows.div_ceil(factor), cols.div_ceil(factor));
+        let mut out = Vec::with_capacity(out_rows * out_cols);
+        for br in 0..out_rows {
+            for bc in 0..out_cols {
+                let (mut sum, mut count) = (0.0, 0);
+                for r in br * factor..rows.min((br + 1) * factor) {
+                    for c in bc * factor..cols.min((bc + 1) * factor) {
+                        let v = values[r * cols + c];
+                        if !v.is_nan() {
+                            sum += v;
+                            count += 1;
+                        }
+                    }
+                }
+                out.push(if count == 0 { f64::NAN } else { sum / count as f64 });
+            }
+        }
+
+        Some((vec![out_rows, out_cols], out))
+    }
+
+    /// Smooths the data with a simple moving average over `window`
+    /// consecutive elements, giving `len - window + 1` values. Fill and
+    /// missing values are left out of each window's mean; windows holding
+    /// only fill average to NaN.
+    pub fn moving_average(&self, window: usize) -> Result<Vec<f64>> {
+        let values = self.to_f64_with_nan_fill();
+        if window == 0 || window > values.len() {
+            return Err(ParseError::new(&format!(
+                "window {} doesn't fit data of length {}", window, values.len()
+            )));
+        }
+
+        Ok(values
+            .windows(window)
+            .map(|w| {
+                let valid: Vec<f64> = w.iter().copied().filter(|v| !v.is_nan()).collect();
+                if valid.is_empty() {
+                    f64::NAN
+                } else {
+                    valid.iter().sum::<f64>() / valid.len() as f64
+                }
+            })
+            .collect())
+    }
+}
+
+impl NCFile {
+    /// Averages the variable `var_name` over its dimension `axis`, ignoring
+    /// fill and missing values. The result has the variable's shape with
+    /// `axis` removed, in row-major order; positions with no valid values
+    /// are NaN. Returns `None` if the variable isn't numeric, doesn't span
+    /// `axis` or holds less data than its shape.
+    pub fn reduce_mean(&self, var_name: &str, axis: &str) -> Option<Vec<f64>> {
+        let (values, [outer, n, inner]) = self.split_axis(var_name, axis)?;
+
+        let mut means = Vec::with_capacity(outer * inner);
+        for o in 0..outer {
+            for i in 0..inner {
+                let (sum, count) = (0..n)
+                    .map(|j| values[(o * n + j) * inner + i])
+                    .filter(|v| !v.is_nan())
+                    .fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+                means.push(if count == 0 { f64::NAN } else { sum / count as f64 });
+            }
+        }
+
+        Some(means)
+    }
+
+    /// Subtracts the mean over the dimension `axis` from every element of
+    /// the variable `var_name`, giving the anomaly field in the variable's
+    /// own shape. Fill and missing values are left out of the means and are
+    /// NaN in the result.
+    pub fn anomaly(&self, var_name: &str, mean_axis: &str) -> Option<Vec<f64>> {
+        let means = self.reduce_mean(var_name, mean_axis)?;
+        let (values, [_, n, inner]) = self.split_axis(var_name, mean_axis)?;
+
+        Some(
+            values
+                .iter()
+                .enumerate()
+                .map(|(idx, v)| v - means[idx / (n * inner) * inner + idx % inner])
+                .collect(),
+        )
+    }
+
+    /// Differentiates the variable `var_name` along the dimension `axis`
+    /// with finite differences: central differences inside the array and
+    /// one-sided differences at its edges. Steps are taken from the axis's
+    /// coordinate variable if it has one, otherwise they're unit steps. An
+    /// axis of length one has a zero gradient. Fill and missing values are
+    /// NaN and spread to their neighbours.
+    pub fn gradient(&self, var_name: &str, axis: &str) -> Option<Vec<f64>> {
+        let (values, [_, n, inner]) = self.split_axis(var_name, axis)?;
+        let coords = match self.coordinate_variable(axis).and_then(|c| c.to_f64_vec()) {
+            Some(c) if c.len() >= n => c,
+            _ => (0..n).map(|i| i as f64).collect(),
+        };
+
+        Some(
+            (0..values.len())
+                .map(|idx| {
+                    let j = idx / inner % n;
+                    let (lo, hi) = (j.saturating_sub(1), (j + 1).min(n - 1));
+                    if lo == hi {
+                        return 0.0;
+                    }
+
+                    let at = |k: usize| values[idx - j * inner + k * inner];
+                    (at(hi) - at(lo)) / (coords[hi] - coords[lo])
+                })
+                .collect(),
+        )
+    }
+
+    /// The Pearson correlation coefficient between the 1-D variables `a` and
+    /// `b`, skipping positions where either holds a fill or missing value.
+    /// Returns `None` if either variable isn't a numeric 1-D variable, their
+    /// lengths differ, or fewer than two positions remain or either has no
+    /// variance.
+    pub fn correlation(&self, a: &str, b: &str) -> Option<f64> {
+        let values = |name: &str| {
+            let var = self.variable(name)?;
+            let [n] = self.resolve_shape(var.dimids())[..] else {
+                return None;
+            };
+            let mut values = each_numeric_variable!(var, c => c.to_f64_with_nan_fill(), _ => return None);
+            if values.len() < n {
+                return None;
+            }
+            values.truncate(n);
+            Some(values)
+        };
+        let (xs, ys) = (values(a)?, values(b)?);
+        if xs.len() != ys.len() {
+            return None;
+        }
+
+        let pairs: Vec<(f64, f64)> = xs
+            .into_iter()
+            .zip(ys)
+            .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+            .collect();
+        if pairs.len() < 2 {
+            return None;
+        }
+
+        let n = pairs.len() as f64;
+        let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+        let (cov, var_x, var_y) = pairs.iter().fold((0.0, 0.0, 0.0), |(c, vx, vy), &(x, y)| {
+            let (dx, dy) = (x - mean_x, y - mean_y);
+            (c + dx * dy, vx + dx * dx, vy + dy * dy)
+        });
+        if var_x == 0.0 || var_y == 0.0 {
+            return None;
+        }
+
+        Some(cov / (var_x * var_y).sqrt())
+    }
+
+    /// The fill-masked values of the variable `var_name`, trimmed to its
+    /// shape, along with the `[outer, n, inner]` extents around the
+    /// dimension `axis`: the element count before it, its length, and the
+    /// element count after it.
+    fn split_axis(&self, var_name: &str, axis: &str) -> Option<(Vec<f64>, [usize; 3])> {
+        let var = self.variable(var_name)?;
+        let k = self.dimension_names(var).iter().position(|&d| d == axis)?;
+        let mut values = each_numeric_variable!(var, c => c.to_f64_with_nan_fill(), _ => return None);
+
+        let shape = self.resolve_shape(var.dimids());
+        let outer: usize = shape[..k].iter().product();
+        let inner: usize = shape[k + 1..].iter().product();
+        let n = shape[k];
+        if values.len() < outer * n * inner {
+            return None;
+        }
+        values.truncate(outer * n * inner);
+
+        Some((values, [outer, n, inner]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn it_computes_quantiles() {
+        let mut v = float_container("x", &[0], &[5.0, 1.0, -999.0, 3.0, 2.0, 4.0]);
+        v.attributes.push(float_attr("_FillValue", &[-999.0]));
+
+        assert_eq!(v.quantile(0.5), Some(3.0));
+        assert_eq!(v.quantile(0.0), Some(1.0));
+        assert_eq!(v.quantile(1.0), Some(5.0));
+        assert_eq!(v.quantile(0.125), Some(1.5));
+
+        let empty = float_container("x", &[0], &[]);
+        assert_eq!(empty.quantile(0.5), None);
+    }
+
+    #[test]
+    fn it_computes_moving_averages() {
+        let mut v = float_container("x", &[0], &[1.0, 2.0, 3.0, -999.0, 5.0, 7.0]);
+        v.attributes.push(float_attr("_FillValue", &[-999.0]));
+
+        assert_eq!(v.moving_average(3).unwrap(), vec![2.0, 2.5, 4.0, 6.0]);
+        assert_eq!(v.moving_average(1).unwrap()[..3], [1.0, 2.0, 3.0]);
+        assert!(v.moving_average(0).is_err());
+        assert!(v.moving_average(7).is_err());
+    }
+
+    #[test]
+    fn it_computes_anomalies() {
+        let mut v = float_container("temp", &[0, 1], &[1.0, 2.0, 3.0, 3.0, -999.0, 6.0]);
+        v.attributes.push(float_attr("_FillValue", &[-999.0]));
+        let f = file(vec![dim("y", 2), dim("x", 3)], vec![NCVariable::Float(v)]);
+
+        let anomaly = f.anomaly("temp", "y").unwrap();
+        assert_eq!(anomaly.len(), 6);
+        assert!(anomaly[4].is_nan());
+        for x in 0..3 {
+            let sum: f64 = (0..2).map(|y| anomaly[y * 3 + x]).filter(|v| !v.is_nan()).sum();
+            assert!(sum.abs() < 1e-9);
+        }
+        assert_eq!(anomaly[..3], [-1.0, 0.0, -1.5]);
+
+        assert_eq!(f.anomaly("temp", "x").unwrap()[..3], [-1.0, 0.0, 1.0]);
+        assert_eq!(f.anomaly("temp", "time"), None);
+    }
+
+    #[test]
+    fn it_downsamples_grids() {
+        let values: Vec<f32> = (0..16).map(|v| v as f32).collect();
+        let v = float_container("x", &[0, 1], &values);
+
+        let (shape, data) = v.downsample(&[4, 4], 2).unwrap();
+        assert_eq!(shape, [2, 2]);
+        assert_eq!(data, [2.5, 4.5, 10.5, 12.5]);
+
+        let (shape, data) = v.downsample(&[4, 4], 3).unwrap();
+        assert_eq!(shape, [2, 2]);
+        assert_eq!(data, [5.0, 7.0, 13.0, 15.0]);
+
+        assert_eq!(v.downsample(&[16], 2), None);
+        assert_eq!(v.downsample(&[4, 4], 0), None);
+        assert_eq!(v.downsample(&[4, 5], 2), None);
+        assert_eq!(v.downsample(&[usize::MAX, 2], 2), None);
+    }
+
+    #[test]
+    fn it_computes_gradients() {
+        let f = file(
+            vec![dim("x", 4)],
+            vec![
+                float_var("x", &[0], &[0.0, 0.5, 1.0, 1.5]),
+                float_var("line", &[0], &[1.0, 4.0, 7.0, 10.0]),
+            ],
+        );
+        assert_eq!(f.gradient("line", "x"), Some(vec![6.0; 4]));
+
+        let f = file(
+            vec![dim("y", 2), dim("x", 3)],
+            vec![float_var("grid", &[0, 1], &[0.0, 1.0, 4.0, 2.0, 3.0, 6.0])],
+        );
+        assert_eq!(f.gradient("grid", "x"), Some(vec![1.0, 2.0, 3.0, 1.0, 2.0, 3.0]));
+        assert_eq!(f.gradient("grid", "y"), Some(vec![2.0; 6]));
+    }
+
+    #[test]
+    fn it_averages_over_an_axis() {
+        let mut v = float_container("temp", &[0, 1], &[1.0, 2.0, 3.0, 3.0, -999.0, 5.0]);
+        v.attributes.push(float_attr("_FillValue", &[-999.0]));
+        let f = file(vec![dim("y", 2), dim("x", 3)], vec![NCVariable::Float(v)]);
+
+        assert_eq!(f.reduce_mean("temp", "y"), Some(vec![2.0, 2.0, 4.0]));
+        assert_eq!(f.reduce_mean("temp", "x"), Some(vec![2.0, 4.0]));
+        assert_eq!(f.reduce_mean("temp", "time"), None);
+    }
+
+    #[test]
+    fn it_rejects_out_of_range_quantiles() {
+        let v = float_container("x", &[0], &[1.0]);
+        assert_eq!(v.quantile(1.5), None);
+        assert_eq!(v.quantile(-0.1), None);
+        assert_eq!(v.quantile(f64::NAN), None);
+    }
+
+    #[test]
+    fn it_correlates_variables() {
+        let mut a = float_container("a", &[0], &[1.0, 2.0, -999.0, 4.0, 8.0]);
+        a.attributes.push(float_attr("_FillValue", &[-999.0]));
+        let f = file(
+            vec![dim("x", 5), dim("y", 2)],
+            vec![
+                NCVariable::Float(a),
+                float_var("b", &[0], &[-2.0, -4.0, 0.0, -8.0, -16.0]),
+                float_var("c", &[0], &[3.0, 3.0, 3.0, 3.0, 3.0]),
+                float_var("d", &[1], &[1.0, 2.0]),
+            ],
+        );
+
+        assert!((f.correlation("a", "a").unwrap() - 1.0).abs() < 1e-12);
+        assert!((f.correlation("a", "b").unwrap() + 1.0).abs() < 1e-12);
+        assert_eq!(f.correlation("a", "c"), None);
+        assert_eq!(f.correlation("a", "d"), None);
+        assert_eq!(f.correlation("a", "missing"), None);
+    }
+
+    #[test]
+    fn it_interpolates_at_fractional_indices() {
+        let mut v = float_container("x", &[0], &[10.0, 20.0, -999.0, 40.0]);
+        v.attributes.push(float_attr("_FillValue", &[-999.0]));
+
+        assert_eq!(v.interp_at(0.5), Some(15.0));
+        assert_eq!(v.interp_at(0.25), Some(12.5));
+        assert_eq!(v.interp_at(1.0), Some(20.0));
+        assert_eq!(v.interp_at(3.0), Some(40.0));
+        assert_eq!(v.interp_at(1.5), None);
+        assert_eq!(v.interp_at(-0.5), None);
+        assert_eq!(v.interp_at(3.5), None);
+        assert_eq!(v.interp_at(f64::NAN), None);
+    }
+}
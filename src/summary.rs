@@ -0,0 +1,137 @@
+use crate::cdl::format_float;
+use crate::reader::*;
+
+/// How many of each kind of header entry a file declares.
+#[derive(Debug, Default, PartialEq)]
+pub struct FileCounts {
+    pub dimensions: usize,
+    pub global_attributes: usize,
+    pub variables: usize,
+    pub total_variable_attributes: usize,
+}
+
+impl NCFile {
+    /// Counts the file's dimensions, global attributes, variables and
+    /// variable attributes in one call.
+    pub fn counts(&self) -> FileCounts {
+        FileCounts {
+            dimensions: self.dimensions.len(),
+            global_attributes: self.attributes.len(),
+            variables: self.variables.len(),
+            total_variable_attributes: self.variables.iter().map(|v| v.attributes().len()).sum(),
+        }
+    }
+
+    /// Flattens the file's metadata into `(key, value)` pairs with keys such
+    /// as `global.Conventions`, `dim.longitude.length` and
+    /// `var.longitude.units`, in header order.
+    pub fn flatten_metadata(&self) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+
+        for a in &self.attributes {
+            out.push((format!("global.{}", a.name()), render_values(a)));
+        }
+
+        for d in &self.dimensions {
+            out.push((format!("dim.{}.length", d.name), d.length.to_string()));
+        }
+
+        for v in &self.variables {
+            each_variable!(v, c => {
+                for a in &c.attributes {
+                    out.push((format!("var.{}.{}", c.name, a.name()), render_values(a)));
+                }
+            });
+        }
+
+        out
+    }
+
+    /// The single-valued numeric attributes of the file widened to `f64`,
+    /// keyed like `flatten_metadata` as `global.NAME` or `var.VAR.NAME`, in
+    /// header order. Multi-valued and `Char` attributes are skipped.
+    pub fn scalar_numeric_attributes(&self) -> Vec<(String, f64)> {
+        let scalar = |a: &NCAttribute| match a.to_f64_vec().as_deref() {
+            Some(&[v]) => Some(v),
+            _ => None,
+        };
+
+        let globals = self
+            .attributes
+            .iter()
+            .filter_map(|a| Some((format!("global.{}", a.name()), scalar(a)?)));
+        let variables = self.variables.iter().flat_map(|v| {
+            v.attributes()
+                .iter()
+                .filter_map(move |a| Some((format!("var.{}.{}", v.name(), a.name()), scalar(a)?)))
+        });
+
+        globals.chain(variables).collect()
+    }
+}
+
+/// Renders an attribute's values as plain text: the string itself for
+/// `Char` attributes, otherwise the values separated by commas.
+pub(crate) fn render_values(a: &NCAttribute) -> String {
+    if let Some(text) = a.as_text() {
+        return text;
+    }
+
+    let values: Vec<String> = match a {
+        NCAttribute::Float(c) => c.values.iter().copied().map(format_float).collect(),
+        NCAttribute::Double(c) => c.values.iter().copied().map(format_float).collect(),
+        _ => each_attribute!(a, c => c.values.iter().map(|v| v.to_string()).collect()),
+    };
+
+    values.join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+    use crate::summary::FileCounts;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    #[test]
+    fn it_flattens_metadata() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let flat = f.flatten_metadata();
+        let has = |k: &str, v: &str| flat.contains(&(k.to_string(), v.to_string()));
+
+        assert!(has("global.Conventions", "CF-1.6"));
+        assert!(has("dim.longitude.length", "700"));
+        assert!(has("var.longitude.units", "degrees_east"));
+        assert!(has("var.unknown._FillValue", "-32767"));
+        assert!(has("var.unknown.scale_factor", "2.4103851115825365e-12"));
+
+        let mut f = f;
+        f.attributes.push(NCAttribute::Float(NCAttributeContainer::new("scale", vec![0.01])));
+        assert!(f.flatten_metadata().contains(&("global.scale".into(), "0.01".into())));
+    }
+
+    #[test]
+    fn it_counts_header_entries() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+
+        assert_eq!(
+            f.counts(),
+            FileCounts {
+                dimensions: 3,
+                global_attributes: 2,
+                variables: 4,
+                total_variable_attributes: 2 + 2 + 3 + 4,
+            }
+        );
+    }
+
+    #[test]
+    fn it_collects_scalar_numeric_attributes() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let scalars = f.scalar_numeric_attributes();
+
+        assert!(scalars.contains(&("var.unknown._FillValue".to_string(), -32767.0)));
+        assert!(scalars.iter().any(|(k, _)| k == "var.unknown.scale_factor"));
+        assert!(!scalars.iter().any(|(k, _)| k == "global.Conventions" || k == "var.longitude.units"));
+    }
+}
@@ -0,0 +1,71 @@
+//! Helpers for building small in-memory files in tests.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::reader::*;
+
+pub fn dim(name: &str, length: u64) -> NCDimension {
+    NCDimension {
+        name: name.to_string(),
+        length,
+    }
+}
+
+pub fn file(dimensions: Vec<NCDimension>, variables: Vec<NCVariable>) -> NCFile {
+    NCFile {
+        version: 1,
+        numrecs: 0,
+        dimensions,
+        attributes: Vec::new(),
+        variables,
+    }
+}
+
+pub fn float_container(name: &str, dimids: &[u32], values: &[f32]) -> NCVariableContainer<f32> {
+    let raw: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes().to_vec()).collect();
+
+    NCVariableContainer {
+        name: name.to_string(),
+        dimids: dimids.to_vec(),
+        attributes: Vec::new(),
+        vsize: raw.len() as u64,
+        offset: 0,
+        data: NCData::new(raw),
+    }
+}
+
+pub fn float_var(name: &str, dimids: &[u32], values: &[f32]) -> NCVariable {
+    NCVariable::Float(float_container(name, dimids, values))
+}
+
+pub fn float_attr(name: &str, values: &[f32]) -> NCAttribute {
+    NCAttribute::Float(NCAttributeContainer::new(name, values.to_vec()))
+}
+
+pub fn char_attr(name: &str, value: &str) -> NCAttribute {
+    NCAttribute::Char(NCAttributeContainer::new(name, value.chars().collect()))
+}
+
+/// A path in the temporary directory unique to this process, removed when
+/// dropped so a failing test doesn't leave the file behind.
+pub struct TempPath(PathBuf);
+
+impl TempPath {
+    pub fn new(name: &str) -> Self {
+        TempPath(std::env::temp_dir().join(format!("libnetcdf-rs-{}-{}", process::id(), name)))
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::reader::*;
+
+/// Variable attributes that CF requires to hold a single value.
+const SCALAR_ATTRIBUTES: [&str; 3] = ["_FillValue", "scale_factor", "add_offset"];
+
+impl NCFile {
+    /// Checks the parsed file against the conventions its values rely on,
+    /// returning an error describing the first violation found.
+    pub fn validate(&self) -> Result<()> {
+        for v in &self.variables {
+            each_variable!(v, c => {
+                for name in SCALAR_ATTRIBUTES.iter() {
+                    match c.attribute(name) {
+                        Some(a) if !a.is_scalar() => {
+                            return Err(ParseError::new(&format!(
+                                "attribute {} of variable {} must be a scalar", name, c.name
+                            )));
+                        }
+                        _ => {}
+                    }
+                }
+
+                if let Some(fill) = c.attribute("_FillValue") {
+                    if fill.nc_type() != v.nc_type() {
+                        return Err(ParseError::new(&format!(
+                            "_FillValue of variable {} is {} but the variable is {}",
+                            c.name, fill.nc_type().name(), v.nc_type().name()
+                        )));
+                    }
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a file of `len` bytes is long enough to hold everything
+    /// the header describes, as given by `total_size`. A shorter file has
+    /// been truncated.
+    pub fn validate_length(&self, len: u64) -> Result<()> {
+        let total = self
+            .total_size()
+            .ok_or_else(|| ParseError::new("the header describes more data than a file can hold"))?;
+        if len < total {
+            return Err(ParseError::new(&format!(
+                "file holds {} bytes but its header describes {}", len, total
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn it_validates_scalar_attributes() {
+        let mut v = float_container("temp", &[0], &[1.0, 2.0]);
+        v.attributes.push(float_attr("scale_factor", &[0.5]));
+        assert!(v.attributes[0].is_scalar());
+
+        let mut f = file(vec![dim("x", 2)], vec![NCVariable::Float(v)]);
+        assert!(f.validate().is_ok());
+
+        if let NCVariable::Float(v) = &mut f.variables[0] {
+            v.attributes[0] = float_attr("scale_factor", &[0.5, 2.0]);
+        }
+        let err = f.validate().unwrap_err();
+        assert_eq!(err.to_string(), "attribute scale_factor of variable temp must be a scalar");
+    }
+
+    #[test]
+    fn it_validates_fill_value_types() {
+        let mut v = float_container("temp", &[0], &[1.0, 2.0]);
+        v.attributes.push(float_attr("_FillValue", &[-1.0]));
+
+        let mut f = file(vec![dim("x", 2)], vec![NCVariable::Float(v)]);
+        assert!(f.validate().is_ok());
+
+        if let NCVariable::Float(v) = &mut f.variables[0] {
+            v.attributes[0] = NCAttribute::Int(NCAttributeContainer::new("_FillValue", vec![-1]));
+        }
+        let err = f.validate().unwrap_err();
+        assert_eq!(err.to_string(), "_FillValue of variable temp is int but the variable is float");
+    }
+
+    #[test]
+    fn it_validates_the_file_length() {
+        let f = NCFile::open("./samples/sample1.nc").unwrap();
+        let len = std::fs::metadata("./samples/sample1.nc").unwrap().len();
+
+        assert!(f.validate_length(len).is_ok());
+        let err = f.validate_length(len - 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("file holds {} bytes but its header describes {}", len - 1, len)
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use std::any::TypeId;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::consts::*;
+use crate::reader::*;
+
+impl NCFile {
+    /// Overwrites the stored data of the variable `name` in place, seeking to
+    /// the offset recorded in its header. The new data must have the same
+    /// element type and occupy the same `vsize` as the original.
+    pub fn overwrite_variable_data<W, T>(&self, w: &mut W, name: &str, data: &NCData<T>) -> Result<()>
+    where
+        W: io::Write + io::Seek,
+        T: 'static,
+    {
+        let var = self
+            .variable(name)
+            .ok_or_else(|| ParseError::new(&format!("no variable named {}", name)))?;
+
+        if self.is_record_variable(var) {
+            return Err(ParseError::new("overwriting record variables is not supported"));
+        }
+
+        let (vsize, offset) = var.layout();
+        if !each_variable!(var, c => data_type(&c.data) == TypeId::of::<T>()) {
+            return Err(ParseError::new(&format!("data type doesn't match variable {}", name)));
+        }
+
+        let len = data.raw().len();
+        if padded_len(len) as u64 != vsize {
+            return Err(ParseError::new(&format!(
+                "data size {} doesn't match vsize {} of variable {}", len, vsize, name
+            )));
+        }
+
+        w.seek(io::SeekFrom::Start(offset))?;
+        w.write_all(data.raw())?;
+        w.write_all(&vec![0; padded_len(len) - len])?;
+
+        Ok(())
+    }
+
+    /// Checks that the parsed structure can be serialized again: names must
+    /// be non-empty and free of NULs, the format version must be one this
+    /// crate writes, every type must be one that version can hold, and every
+    /// variable must hold exactly the data its shape calls for, which its
+    /// `vsize` covers once padded. Returns an error describing the first
+    /// problem found.
+    pub fn writable(&self) -> Result<()> {
+        if !matches!(self.version, 1 | 2 | VERSION_CDF5) {
+            return Err(ParseError::new(&format!("unsupported format version {}", self.version)));
+        }
+
+        for d in &self.dimensions {
+            check_name("dimension", &d.name)?;
+        }
+        for a in &self.attributes {
+            check_name("attribute", a.name())?;
+            self.check_type("attribute", a.name(), a.nc_type())?;
+        }
+
+        for v in &self.variables {
+            let size = v.nc_type().size();
+            let elements: usize = v
+                .dimids()
+                .iter()
+                .filter_map(|&id| self.dimension_by_id(id))
+                .filter(|d| d.length != 0)
+                .map(|d| d.length as usize)
+                .product();
+            let expected = elements * size;
+
+            each_variable!(v, c => {
+                check_name("variable", &c.name)?;
+                self.check_type("variable", &c.name, v.nc_type())?;
+                for a in &c.attributes {
+                    check_name("attribute", a.name())?;
+                    self.check_type("attribute", a.name(), a.nc_type())?;
+                }
+
+                if c.dimids.iter().any(|&id| id as usize >= self.dimensions.len()) {
+                    return Err(ParseError::new(&format!(
+                        "variable {} refers to an undefined dimension", c.name
+                    )));
+                }
+
+                let len = c.data.raw().len();
+                if self.is_record_variable(v) {
+                    let records = self.data_extent(v).total();
+                    if c.data.partial || len != records {
+                        return Err(ParseError::new(&format!(
+                            "variable {} holds {} bytes but its records need {}", c.name, len, records
+                        )));
+                    }
+                    continue;
+                }
+                if c.data.partial || padded_len(len) as u64 != c.vsize {
+                    return Err(ParseError::new(&format!(
+                        "variable {} holds {} bytes but its vsize is {}", c.name, len, c.vsize
+                    )));
+                }
+                if len != expected {
+                    return Err(ParseError::new(&format!(
+                        "variable {} holds {} bytes but its shape needs {}", c.name, len, expected
+                    )));
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Writes a file with no records, dimensions, attributes or variables in
+    /// the format `version`, which must be 1, 2 or 5 (CDF-5).
+    pub fn write_empty<W: io::Write + io::Seek>(w: &mut W, version: u8) -> Result<()> {
+        // counts are 8 bytes wide in CDF-5
+        let zero: &[u8] = match version {
+            1 | 2 => &[0; 4],
+            VERSION_CDF5 => &[0; 8],
+            _ => return Err(ParseError::new(&format!("unsupported format version {}", version))),
+        };
+
+        w.write_all(MAGIC_NUMBER.as_bytes())?;
+        w.write_all(&[version])?;
+        // numrecs
+        w.write_all(zero)?;
+
+        // absent dimension, attribute and variable lists
+        for _ in 0..3 {
+            w.write_all(&u32::from(ZERO).to_be_bytes())?;
+            w.write_all(zero)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sorts the global attributes and those of every variable by name so
+    /// that structurally equal files serialize identically. The sort is
+    /// stable, so attributes sharing a name keep their relative order.
+    pub fn sort_attributes(&mut self) {
+        self.attributes.sort_by(|a, b| a.name().cmp(b.name()));
+
+        for v in &mut self.variables {
+            v.attributes_mut().sort_by(|a, b| a.name().cmp(b.name()));
+        }
+    }
+}
+
+impl NCVariable {
+    /// Writes the variable's stored big-endian bytes, as they appear in the
+    /// file but without the padding after the last element, to a standalone
+    /// file at `path`.
+    pub fn dump_raw_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        each_variable!(self, c => fs::write(path, c.data.raw())?);
+        Ok(())
+    }
+
+    /// Writes the variable's decoded values to a standalone file at `path`
+    /// using the native byte order of this machine.
+    pub fn dump_native_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let bytes: Vec<u8> = match self {
+            NCVariable::Byte(c) => c.data.raw().to_vec(),
+            NCVariable::UByte(c) => c.data.raw().to_vec(),
+            NCVariable::Char(c) => c.data.raw().to_vec(),
+            NCVariable::Short(c) => c.data.iter().flat_map(i16::to_ne_bytes).collect(),
+            NCVariable::Int(c) => c.data.iter().flat_map(i32::to_ne_bytes).collect(),
+            NCVariable::Float(c) => c.data.iter().flat_map(f32::to_ne_bytes).collect(),
+            NCVariable::Double(c) => c.data.iter().flat_map(f64::to_ne_bytes).collect(),
+            NCVariable::UShort(c) => c.data.iter().flat_map(u16::to_ne_bytes).collect(),
+            NCVariable::UInt(c) => c.data.iter().flat_map(u32::to_ne_bytes).collect(),
+            NCVariable::Int64(c) => c.data.iter().flat_map(i64::to_ne_bytes).collect(),
+            NCVariable::UInt64(c) => c.data.iter().flat_map(u64::to_ne_bytes).collect(),
+        };
+
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+impl NCFile {
+    /// Fails if the file's format version can't hold values of type `t`:
+    /// the unsigned and 64-bit integer types need CDF-5.
+    fn check_type(&self, kind: &str, name: &str, t: NCType) -> Result<()> {
+        if self.version != VERSION_CDF5 && t.code() > NC_DOUBLE {
+            return Err(ParseError::new(&format!(
+                "{} {} has type {}, which format version {} can't hold",
+                kind,
+                name,
+                t.name(),
+                self.version
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn check_name(kind: &str, name: &str) -> Result<()> {
+    if name.is_empty() || name.contains('\0') {
+        return Err(ParseError::new(&format!("invalid {} name {:?}", kind, name)));
+    }
+    Ok(())
+}
+
+fn data_type<T: 'static>(_: &NCData<T>) -> TypeId {
+    TypeId::of::<T>()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::io::Cursor;
+
+    use crate::reader::*;
+    use crate::test_util::*;
+
+    const SAMPLE_FILE_1: &str = "./samples/sample1.nc";
+
+    #[test]
+    fn it_overwrites_variable_data() {
+        let mut cursor = Cursor::new(fs::read(SAMPLE_FILE_1).unwrap());
+        let f = NCFile::new(&mut cursor).unwrap();
+
+        let values: Vec<u8> = (0..700)
+            .flat_map(|i| (i as f32 * 0.5).to_be_bytes().to_vec())
+            .collect();
+        f.overwrite_variable_data(&mut cursor, "longitude", &NCData::<f32>::new(values)).unwrap();
+
+        cursor.set_position(0);
+        let g = NCFile::new(&mut cursor).unwrap();
+        if let NCVariable::Float(n) = &g.variables[0] {
+            let mut iter = n.data.iter();
+            assert_eq!(iter.next().unwrap(), 0.0);
+            assert_eq!(iter.next().unwrap(), 0.5);
+            assert_eq!(iter.next_back().unwrap(), 349.5);
+        } else {
+            panic!("first variable isn't Float");
+        }
+
+        if let NCVariable::Float(n) = &g.variables[1] {
+            assert_eq!(n.data.iter().next().unwrap(), 69.95);
+        }
+    }
+
+    #[test]
+    fn it_rejects_mismatched_data() {
+        let mut cursor = Cursor::new(fs::read(SAMPLE_FILE_1).unwrap());
+        let f = NCFile::new(&mut cursor).unwrap();
+
+        let short = NCData::<f32>::new(vec![0; 8]);
+        assert!(f.overwrite_variable_data(&mut cursor, "longitude", &short).is_err());
+
+        let wrong_type = NCData::<i32>::new(vec![0; 2800]);
+        assert!(f.overwrite_variable_data(&mut cursor, "longitude", &wrong_type).is_err());
+    }
+
+    #[test]
+    fn it_dumps_variable_data() {
+        let f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        let raw = TempPath::new("dump-raw.bin");
+        let native = TempPath::new("dump-native.bin");
+
+        f.variables[0].dump_raw_to(&raw).unwrap();
+        f.variables[0].dump_native_to(&native).unwrap();
+
+        let (vsize, _) = f.variables[0].layout();
+        assert_eq!(fs::metadata(&raw).unwrap().len(), vsize);
+
+        let bytes = fs::read(&native).unwrap();
+        assert_eq!(bytes.len() as u64, vsize);
+        assert_eq!(f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]), -24.95);
+    }
+
+    #[test]
+    fn it_sorts_attributes() {
+        let mut v = float_container("temp", &[], &[0.0]);
+        v.attributes.push(float_attr("units", &[1.0]));
+        v.attributes.push(float_attr("_FillValue", &[2.0]));
+        v.attributes.push(float_attr("long_name", &[3.0]));
+
+        let mut f = file(Vec::new(), vec![NCVariable::Float(v)]);
+        f.attributes.push(float_attr("title", &[0.0]));
+        f.attributes.push(float_attr("Conventions", &[0.0]));
+        f.attributes.push(float_attr("history", &[0.0]));
+        f.sort_attributes();
+
+        let names: Vec<&str> = f.attributes.iter().map(|a| a.name()).collect();
+        assert_eq!(names, vec!["Conventions", "history", "title"]);
+
+        let names: Vec<&str> = f.variables[0].attributes().iter().map(|a| a.name()).collect();
+        assert_eq!(names, vec!["_FillValue", "long_name", "units"]);
+    }
+
+    #[test]
+    fn it_checks_files_are_writable() {
+        assert!(NCFile::open(SAMPLE_FILE_1).unwrap().writable().is_ok());
+
+        let mut f = file(vec![dim("x", 4)], vec![float_var("temp", &[0], &[1.0, 2.0, 3.0])]);
+        let err = f.writable().unwrap_err();
+        assert_eq!(err.to_string(), "variable temp holds 12 bytes but its shape needs 16");
+
+        f.dimensions[0] = dim("x\0", 3);
+        let err = f.writable().unwrap_err();
+        assert_eq!(err.to_string(), "invalid dimension name \"x\\0\"");
+
+        let mut f = NCFile::open(SAMPLE_FILE_1).unwrap();
+        f.variables[0]
+            .attributes_mut()
+            .push(NCAttribute::UShort(NCAttributeContainer::new("flags", vec![1])));
+        let err = f.writable().unwrap_err().to_string();
+        assert_eq!(err, "attribute flags has type ushort, which format version 2 can't hold");
+
+        let mut f = file(vec![dim("x", 1)], Vec::new());
+        f.variables.push(NCVariable::Int64(NCVariableContainer {
+            name: String::from("id"),
+            dimids: vec![0],
+            attributes: Vec::new(),
+            vsize: 8,
+            offset: 0,
+            data: NCData::new(vec![0; 8]),
+        }));
+        let err = f.writable().unwrap_err().to_string();
+        assert_eq!(err, "variable id has type int64, which format version 1 can't hold");
+        f.version = 5;
+        assert!(f.writable().is_ok());
+        f.version = 3;
+        assert_eq!(f.writable().unwrap_err().to_string(), "unsupported format version 3");
+
+        assert!(NCFile::open("./samples/sample3.nc").unwrap().writable().is_ok());
+
+        // a short variable of 3 elements, padded to a vsize of 8 on disk
+        let short = |raw: Vec<u8>| {
+            NCVariable::Short(NCVariableContainer {
+                name: String::from("s"),
+                dimids: vec![0],
+                attributes: Vec::new(),
+                vsize: 8,
+                offset: 0,
+                data: NCData::new(raw),
+            })
+        };
+        let mut f = file(vec![dim("x", 3)], vec![short(vec![0, 1, 0, 2, 0, 3])]);
+        assert!(f.writable().is_ok());
+        f.variables[0] = short(vec![0, 1, 0, 2, 0, 3, 0, 0]);
+        let err = f.writable().unwrap_err().to_string();
+        assert_eq!(err, "variable s holds 8 bytes but its shape needs 6");
+    }
+
+    #[test]
+    fn it_writes_empty_files() {
+        for &version in &[1, 2, 5] {
+            let mut w = Cursor::new(Vec::new());
+            NCFile::write_empty(&mut w, version).unwrap();
+            assert_eq!(w.get_ref().len(), if version == 5 { 48 } else { 32 });
+
+            w.set_position(0);
+            let f = NCFile::new(&mut w).unwrap();
+            assert_eq!(f.version, version);
+            assert_eq!(f.numrecs, 0);
+            assert!(f.dimensions.is_empty() && f.attributes.is_empty() && f.variables.is_empty());
+            assert_eq!(f.header_len(), w.get_ref().len() as u64);
+        }
+
+        assert!(NCFile::write_empty(&mut Cursor::new(Vec::new()), 3).is_err());
+    }
+}